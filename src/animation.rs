@@ -0,0 +1,454 @@
+use std::f32::consts::PI;
+use std::fmt::{self, Display};
+use std::io::{self, IsTerminal, Write};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::ansi::{self, Segment};
+use crate::color::Color;
+use crate::gradient::{Easing, Gradient, GradientSpace, HueArc, Spread};
+use crate::style::Style;
+use crate::term::{self, ColorChoice};
+use crate::width::{display_width, grapheme_width, graphemes};
+
+/// what an [`Animation`] does to its text over time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Effect {
+    /// the text appears out of the background in `color`
+    FadeIn(Color),
+    /// the text in `color` disappears into the background
+    FadeOut(Color),
+    /// the text in `color` dims towards the background and back
+    Pulse(Color),
+    /// the text in `base` with a `highlight` sweeping over it
+    Shimmer { base: Color, highlight: Color },
+    /// the text appears a character at a time, keeping its own styles
+    Typewriter,
+    /// the colors of the rainbow scrolling along the text
+    Rainbow,
+}
+
+/// a line of text re-rendered over time
+///
+/// one run of the effect takes the animation's duration, with the easing
+/// remapping how far along it is. [`Animation::play`] draws the frames in
+/// place at the frame rate, while [`Animation::frames`] and
+/// [`Animation::frame_at`] give them as strings without any waiting, for
+/// tests or to draw them some other way. when colors are off for the
+/// output only the last frame is drawn
+///
+/// example:
+/// ```rs
+/// Animation::new("Ready.", Effect::FadeIn(Color::from(0x22c55e)))
+///     .duration(Duration::from_millis(800))
+///     .easing(Easing::EaseOut)
+///     .play()?;
+///
+/// let title = Animation::new("beautify", Effect::Shimmer {
+///     base: Color::Ansi256(244),
+///     highlight: Color::Rgb(255, 255, 255),
+/// });
+/// let frames: Vec<String> = title.fps(10).frames().collect();
+/// ```
+pub struct Animation {
+    text: String,
+    effect: Effect,
+    duration: Duration,
+    fps: u32,
+    easing: Easing,
+    background: Color,
+    repeat: u32,
+    out: Box<dyn Write + Send>,
+    terminal: bool,
+}
+
+impl Animation {
+    /// `text` with `effect`, a second long at 30 frames per second, drawn
+    /// to stdout
+    pub fn new(text: impl Display, effect: Effect) -> Self {
+        Animation {
+            text: text.to_string(),
+            effect,
+            duration: Duration::from_secs(1),
+            fps: 30,
+            easing: Easing::default(),
+            background: Color::Rgb(0, 0, 0),
+            repeat: 1,
+            out: Box::new(io::stdout()),
+            terminal: io::stdout().is_terminal(),
+        }
+    }
+
+    /// how long one run of the effect takes
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// frames drawn per second, at least 1
+    pub fn fps(mut self, fps: u32) -> Self {
+        self.fps = fps.max(1);
+        self
+    }
+
+    /// remaps how far along the effect is
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// the color text fades into, black by default since the terminal's
+    /// own background can't be asked for
+    pub fn background(mut self, color: Color) -> Self {
+        self.background = color;
+        self
+    }
+
+    /// runs the effect `times` times in a row, at least once
+    pub fn repeat(mut self, times: u32) -> Self {
+        self.repeat = times.max(1);
+        self
+    }
+
+    /// where the animation is played, stdout by default, taken as not
+    /// being a terminal
+    pub fn output(mut self, out: impl Write + Send + 'static) -> Self {
+        self.out = Box::new(out);
+        self.terminal = false;
+        self
+    }
+
+    /// whether the output set with [`Animation::output`] is a terminal,
+    /// which plays every frame under `ColorChoice::Auto`
+    pub fn terminal(mut self, terminal: bool) -> Self {
+        self.terminal = terminal;
+        self
+    }
+
+    /// frames in one run of the effect, not counting the first
+    fn steps(&self) -> u32 {
+        let steps = (self.duration.as_secs_f32() * self.fps as f32).round() as u32;
+        steps.max(1)
+    }
+
+    /// every frame of the animation from start to end, each run of the
+    /// effect ends on its last frame
+    pub fn frames(&self) -> impl Iterator<Item = String> + '_ {
+        let steps = self.steps();
+        (0..=steps * self.repeat).map(move |i| {
+            let t = match (i, i % steps) {
+                (0, _) => 0.0,
+                (_, 0) => 1.0,
+                (_, step) => step as f32 / steps as f32,
+            };
+            self.frame_at(t)
+        })
+    }
+
+    /// the frame `t` of the way through a run, from `0.0` to `1.0`
+    pub fn frame_at(&self, t: f32) -> String {
+        let t = self.easing.apply(t.clamp(0.0, 1.0));
+        let mix = |from, to, t| GradientSpace::Oklab.mix(from, to, t);
+        match self.effect {
+            Effect::Typewriter => typewriter(&self.text, t),
+            _ if !term::colors_enabled() => self.text.clone(),
+            Effect::FadeIn(color) => self.paint(mix(self.background, color, t)),
+            Effect::FadeOut(color) => self.paint(mix(color, self.background, t)),
+            Effect::Pulse(color) => {
+                // halfway to the background at the dimmest
+                let dim = 0.5 * (1.0 - (2.0 * PI * t).cos()) / 2.0;
+                self.paint(mix(color, self.background, dim))
+            }
+            Effect::Shimmer { base, highlight } => {
+                // the highlight runs in from the left and out to the right
+                let cols = display_width(&self.text) as f32;
+                let half = (cols / 6.0).max(2.0);
+                let center = -half + t * (cols + 2.0 * half);
+                paint_cells(&self.text, |x| {
+                    let strength = 1.0 - ((x as f32 + 0.5 - center).abs() / half);
+                    mix(base, highlight, strength.max(0.0))
+                })
+            }
+            Effect::Rainbow => {
+                let rainbow = rainbow();
+                let cols = display_width(&self.text).max(1) as f32;
+                paint_cells(&self.text, |x| rainbow.sample(x as f32 / cols - t))
+            }
+        }
+    }
+
+    fn paint(&self, color: Color) -> String {
+        Style::new().fg(color).paint(&self.text).to_string()
+    }
+
+    /// draws every frame in place at the frame rate, waiting for the end
+    pub fn play(mut self) -> io::Result<()> {
+        // decided for the output rather than the color stream
+        if !term::colors_enabled_for(self.terminal) {
+            let last = term::with_color_choice(ColorChoice::Never, || self.frames().last());
+            return writeln!(self.out, "{}", last.unwrap_or_default());
+        }
+
+        let frames: Vec<String> =
+            term::with_color_choice(ColorChoice::Always, || self.frames().collect());
+        let interval = Duration::from_secs_f64(1.0 / self.fps as f64);
+        let start = Instant::now();
+        for (i, frame) in frames.iter().enumerate() {
+            // frames are timed from the start so slow writes don't add up
+            let due = start + interval * i as u32;
+            if let Some(wait) = due.checked_duration_since(Instant::now()) {
+                thread::sleep(wait);
+            }
+            write!(self.out, "\r{}\x1B[K", frame)?;
+            self.out.flush()?;
+        }
+        writeln!(self.out)
+    }
+}
+
+impl fmt::Debug for Animation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Animation")
+            .field("text", &self.text)
+            .field("effect", &self.effect)
+            .field("duration", &self.duration)
+            .field("fps", &self.fps)
+            .field("easing", &self.easing)
+            .field("background", &self.background)
+            .field("repeat", &self.repeat)
+            .field("terminal", &self.terminal)
+            .finish_non_exhaustive()
+    }
+}
+
+/// the colors of the rainbow, built once
+fn rainbow() -> &'static Gradient {
+    static RAINBOW: OnceLock<Gradient> = OnceLock::new();
+    RAINBOW.get_or_init(|| {
+        Gradient::even([
+            Color::Rgb(255, 0, 0),
+            Color::Rgb(255, 128, 0),
+            Color::Rgb(255, 255, 0),
+            Color::Rgb(0, 255, 0),
+            Color::Rgb(0, 255, 255),
+            Color::Rgb(0, 0, 255),
+            Color::Rgb(255, 0, 255),
+            Color::Rgb(255, 0, 0),
+        ])
+        .space(GradientSpace::Hsl(HueArc::Shorter))
+        .spread(Spread::Repeat)
+    })
+}
+
+/// colors every visible character by its column, escapes in the text are
+/// kept but its own colors are painted over
+fn paint_cells(text: &str, color: impl Fn(usize) -> Color) -> String {
+    let mut result = String::new();
+    let mut x = 0;
+    let mut active = Style::new();
+    // the color last set, escapes from the text may have changed it since
+    let mut last = None;
+    for segment in ansi::segments(text) {
+        match segment {
+            Segment::Escape(e) => {
+                ansi::track_sgr(&mut active, e);
+                result.push_str(e);
+                last = None;
+            }
+            Segment::Text(t) => {
+                for g in graphemes(t) {
+                    if g == "\n" || g == "\r\n" {
+                        x = 0;
+                        result.push_str(g);
+                        continue;
+                    }
+                    let prefix = Style::new().fg(color(x)).prefix();
+                    if last.as_ref() != Some(&prefix) {
+                        result.push_str(&prefix);
+                        last = Some(prefix);
+                    }
+                    result.push_str(g);
+                    x += grapheme_width(g);
+                }
+            }
+        }
+    }
+    if last.is_some() || !active.is_plain() {
+        result.push_str("\x1B[0m");
+    }
+    result
+}
+
+/// the first `t` of the characters of `text`, escapes are all kept so its
+/// styles stay intact
+fn typewriter(text: &str, t: f32) -> String {
+    let total = ansi::segments(text)
+        .map(|segment| match segment {
+            Segment::Text(t) => graphemes(t).count(),
+            Segment::Escape(_) => 0,
+        })
+        .sum::<usize>();
+    let mut left = (t * total as f32).round() as usize;
+
+    let mut result = String::new();
+    for segment in ansi::segments(text) {
+        match segment {
+            Segment::Escape(e) => result.push_str(e),
+            Segment::Text(t) => {
+                for g in graphemes(t).take(left) {
+                    result.push_str(g);
+                    left -= 1;
+                }
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::term::{colored, with_color_choice, ColorChoice};
+    use std::sync::{Arc, Mutex};
+
+    /// an output the test can read back
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Shared {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    #[test]
+    fn typewriter_reveals_a_character_per_frame() {
+        let animation = Animation::new("hey", Effect::Typewriter).fps(3);
+        let frames: Vec<String> = animation.frames().collect();
+        assert_eq!(frames, ["", "h", "he", "hey"]);
+        let styled = Animation::new("\x1B[1mhey\x1B[0m", Effect::Typewriter);
+        assert_eq!(styled.frame_at(1.0 / 3.0), "\x1B[1mh\x1B[0m");
+    }
+
+    #[test]
+    fn repeats_end_every_run_on_its_last_frame() {
+        let animation = Animation::new("hey", Effect::Typewriter).fps(3).repeat(2);
+        let frames: Vec<String> = animation.frames().collect();
+        assert_eq!(frames, ["", "h", "he", "hey", "h", "he", "hey"]);
+    }
+
+    #[test]
+    fn fades_end_on_their_colors() {
+        let color = Color::Rgb(34, 197, 94);
+        let painted = |c: Color| Style::new().fg(c).paint("hey").to_string();
+        colored(|| {
+            let fade_in = Animation::new("hey", Effect::FadeIn(color));
+            assert_eq!(fade_in.frame_at(1.0), painted(color));
+            assert_eq!(fade_in.frame_at(0.0), painted(Color::Rgb(0, 0, 0)));
+            let fade_out = Animation::new("hey", Effect::FadeOut(color));
+            assert_eq!(fade_out.frames().next(), Some(painted(color)));
+            assert_eq!(fade_out.frames().last(), Some(painted(Color::Rgb(0, 0, 0))));
+        });
+        let plain = with_color_choice(ColorChoice::Never, || {
+            Animation::new("hey", Effect::FadeIn(color)).frame_at(0.5)
+        });
+        assert_eq!(plain, "hey");
+    }
+
+    #[test]
+    fn pulses_dim_halfway_and_come_back() {
+        let color = Color::Rgb(34, 197, 94);
+        let painted = |c: Color| Style::new().fg(c).paint("hey").to_string();
+        colored(|| {
+            let pulse = Animation::new("hey", Effect::Pulse(color));
+            assert_eq!(pulse.frame_at(0.0), painted(color));
+            assert_eq!(pulse.frame_at(1.0), painted(color));
+            let dimmest = GradientSpace::Oklab.mix(color, Color::Rgb(0, 0, 0), 0.5);
+            assert_eq!(pulse.frame_at(0.5), painted(dimmest));
+        });
+    }
+
+    #[test]
+    fn shimmers_sweep_the_highlight_across() {
+        let (base, highlight) = (Color::Rgb(100, 100, 100), Color::Rgb(255, 255, 255));
+        let prefix = |c: Color| Style::new().fg(c).prefix();
+        colored(|| {
+            let shimmer = Animation::new("hey", Effect::Shimmer { base, highlight });
+            // the highlight starts and ends off the text
+            let before = format!("{}hey\x1B[0m", prefix(base));
+            assert_eq!(shimmer.frame_at(0.0), before);
+            assert_eq!(shimmer.frame_at(1.0), before);
+            // and is right over the middle halfway
+            let middle = shimmer.frame_at(0.5);
+            assert!(
+                middle.contains(&format!("{}e", prefix(highlight))),
+                "{:?}",
+                middle
+            );
+        });
+    }
+
+    #[test]
+    fn rainbows_scroll_round_to_where_they_started() {
+        colored(|| {
+            let rainbow = Animation::new("rainbow", Effect::Rainbow);
+            let first = rainbow.frame_at(0.0);
+            let red = Style::new().fg(Color::Rgb(255, 0, 0)).prefix();
+            assert!(first.starts_with(&format!("{}r", red)), "{:?}", first);
+            assert!(first.ends_with("w\x1B[0m"), "{:?}", first);
+            assert_eq!(rainbow.frame_at(1.0), first);
+            assert_ne!(rainbow.frame_at(0.5), first);
+        });
+        let plain = with_color_choice(ColorChoice::Never, || {
+            Animation::new("rainbow", Effect::Rainbow).frame_at(0.5)
+        });
+        assert_eq!(plain, "rainbow");
+    }
+
+    #[test]
+    fn cells_are_only_reset_when_colored() {
+        assert_eq!(paint_cells("", |_| Color::Rgb(255, 0, 0)), "");
+        assert_eq!(paint_cells("\n", |_| Color::Rgb(255, 0, 0)), "\n");
+        let closed = colored(|| paint_cells("a\x1B[0m", |_| Color::Rgb(255, 0, 0)));
+        assert_eq!(closed, "\x1B[38;2;255;0;0ma\x1B[0m");
+    }
+
+    #[test]
+    fn play_draws_every_frame_on_a_terminal() {
+        let out = Shared::default();
+        colored(|| {
+            Animation::new("hey", Effect::Typewriter)
+                .duration(Duration::from_millis(30))
+                .fps(100)
+                .output(out.clone())
+                .terminal(true)
+                .play()
+                .unwrap()
+        });
+        assert_eq!(out.text(), "\r\x1B[K\rh\x1B[K\rhe\x1B[K\rhey\x1B[K\n");
+    }
+
+    #[test]
+    fn play_draws_only_the_last_frame_elsewhere() {
+        let out = Shared::default();
+        with_color_choice(ColorChoice::Auto, || {
+            Animation::new("hey", Effect::FadeIn(Color::Rgb(34, 197, 94)))
+                .output(out.clone())
+                .play()
+                .unwrap()
+        });
+        assert_eq!(out.text(), "hey\n");
+    }
+}
@@ -0,0 +1,400 @@
+use crate::color::Color;
+use crate::style::{Attributes, Style};
+
+/// a piece of a string, either plain text or a single escape sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Segment<'a> {
+    Text(&'a str),
+    Escape(&'a str),
+}
+
+/// splits `s` into text and escape sequences
+pub(crate) fn segments(s: &str) -> Segments<'_> {
+    Segments { rest: s }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Segments<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for Segments<'a> {
+    type Item = Segment<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+
+        let len = match self.rest.find('\x1B') {
+            Some(0) => escape_len(self.rest),
+            Some(i) => {
+                let (text, rest) = self.rest.split_at(i);
+                self.rest = rest;
+                return Some(Segment::Text(text));
+            }
+            None => {
+                let text = self.rest;
+                self.rest = "";
+                return Some(Segment::Text(text));
+            }
+        };
+
+        let (escape, rest) = self.rest.split_at(len);
+        self.rest = rest;
+        Some(Segment::Escape(escape))
+    }
+}
+
+/// length of the escape sequence at the start of `s`, which starts with ESC
+fn escape_len(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    match bytes.get(1) {
+        // CSI: parameters and intermediates up to a final byte in @..~
+        Some(b'[') => bytes[2..]
+            .iter()
+            .position(|b| (0x40..=0x7E).contains(b))
+            .map_or(bytes.len(), |i| i + 3),
+        // OSC, DCS and friends: up to BEL or ST (ESC \)
+        Some(b']') | Some(b'P') | Some(b'X') | Some(b'^') | Some(b'_') => {
+            let mut i = 2;
+            while i < bytes.len() {
+                match bytes[i] {
+                    0x07 => return i + 1,
+                    0x1B if bytes.get(i + 1) == Some(&b'\\') => return i + 2,
+                    _ => i += 1,
+                }
+            }
+            bytes.len()
+        }
+        // two character escape, keep multi-byte chars whole
+        Some(_) => 1 + s[1..].chars().next().map_or(0, char::len_utf8),
+        None => 1,
+    }
+}
+
+/// whether `escape` is an sgr sequence that resets every attribute
+pub(crate) fn is_reset(escape: &str) -> bool {
+    escape == "\x1B[0m" || escape == "\x1B[m"
+}
+
+/// whether `escape` is an sgr sequence
+pub(crate) fn is_sgr(escape: &str) -> bool {
+    escape.starts_with("\x1B[") && escape.ends_with('m')
+}
+
+/// splits `s` into text and escape sequences, decoding sgr sequences
+///
+/// example:
+/// ```rs
+/// for token in tokens(&"hi!".text_red()) {
+///     match token {
+///         Token::Text(text) => print!("{}", text),
+///         Token::Sgr(sgr) => eprintln!("style: {}", sgr.apply(Style::new())),
+///         _ => {}
+///     }
+/// }
+/// ```
+pub fn tokens(s: &str) -> Tokens<'_> {
+    Tokens {
+        segments: segments(s),
+        style: Style::new(),
+    }
+}
+
+/// iterator over the [`Token`]s of a string, see [`tokens`]
+#[derive(Debug, Clone)]
+pub struct Tokens<'a> {
+    segments: Segments<'a>,
+    style: Style,
+}
+
+impl Tokens<'_> {
+    /// the style in effect after the tokens read so far
+    pub fn style(&self) -> Style {
+        self.style
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let escape = match self.segments.next()? {
+            Segment::Text(text) => return Some(Token::Text(text)),
+            Segment::Escape(escape) => escape,
+        };
+
+        let token = match escape.as_bytes().get(1) {
+            Some(b'[')
+                if is_sgr(escape)
+                    && escape[2..escape.len() - 1]
+                        .bytes()
+                        .all(|b| b.is_ascii_digit() || b == b';' || b == b':') =>
+            {
+                let sgr = Sgr { raw: escape };
+                self.style = sgr.apply(self.style);
+                Token::Sgr(sgr)
+            }
+            Some(b'[') => Token::Csi(escape),
+            Some(b']') => Token::Osc(escape),
+            _ => Token::Escape(escape),
+        };
+        Some(token)
+    }
+}
+
+/// `s` with every escape sequence removed
+///
+/// example:
+/// ```rs
+/// assert_eq!(strip_ansi(&"hi!".text_red().bold()), "hi!");
+/// ```
+pub fn strip_ansi(s: &str) -> String {
+    let mut text = String::with_capacity(s.len());
+    for token in tokens(s) {
+        if let Token::Text(t) = token {
+            text.push_str(t);
+        }
+    }
+    text
+}
+
+/// number of characters in `s` that aren't part of an escape sequence
+///
+/// this counts chars, use [`display_width`](crate::display_width) for the
+/// number of terminal columns
+///
+/// example:
+/// ```rs
+/// assert_eq!(visible_len(&"hi!".text_red()), 3);
+/// ```
+pub fn visible_len(s: &str) -> usize {
+    segments(s)
+        .map(|segment| match segment {
+            Segment::Text(text) => text.chars().count(),
+            Segment::Escape(_) => 0,
+        })
+        .sum()
+}
+
+/// splits `s` into lines that each stand on their own: sgr sequences still
+/// in effect at a line break are closed at the end of the line and opened
+/// again at the start of the next one
+pub(crate) fn lines(s: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    // the style from the text that is still in effect
+    let mut active = Style::new();
+
+    for segment in segments(s) {
+        let text = match segment {
+            Segment::Escape(e) => {
+                track_sgr(&mut active, e);
+                line.push_str(e);
+                continue;
+            }
+            Segment::Text(t) => t,
+        };
+
+        let mut parts = text.split('\n');
+        if let Some(first) = parts.next() {
+            line.push_str(first);
+        }
+        for part in parts {
+            if line.ends_with('\r') {
+                line.pop();
+            }
+            if !active.is_plain() {
+                line.push_str("\x1B[0m");
+            }
+            lines.push(std::mem::replace(&mut line, active.prefix()));
+            line.push_str(part);
+        }
+    }
+    if !active.is_plain() {
+        line.push_str("\x1B[0m");
+    }
+    lines.push(line);
+    lines
+}
+
+/// updates `active`, the style in effect, with `escape`
+pub(crate) fn track_sgr(active: &mut Style, escape: &str) {
+    if let Some(sgr) = Sgr::parse(escape) {
+        *active = sgr.apply(*active);
+    }
+}
+
+/// byte offset just past the last parameter in an sgr parameter list that
+/// resets every attribute, skipping the arguments of extended colors so the
+/// `0` in `38;5;0` doesn't count
+pub(crate) fn last_reset(params: &str) -> Option<usize> {
+    let mut last = None;
+    let mut skip = 0;
+    let mut extended = false;
+    let mut offset = 0;
+    for param in params.split(';') {
+        let end = offset + param.len();
+        if skip > 0 {
+            skip -= 1;
+        } else if extended {
+            // 5 picks a palette index, 2 an rgb triple
+            skip = match param {
+                "5" => 1,
+                "2" => 3,
+                _ => 0,
+            };
+            extended = false;
+        } else if param.bytes().all(|b| b == b'0') {
+            last = Some(end);
+        } else {
+            extended = matches!(param, "38" | "48" | "58");
+        }
+        offset = end + 1;
+    }
+    last
+}
+
+/// a piece of a string as a terminal sees it
+///
+/// every token borrows its exact bytes from the input, so writing the
+/// tokens back out in order gives the original string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token<'a> {
+    /// visible text
+    Text(&'a str),
+    /// a select graphic rendition sequence, which changes colors and
+    /// attributes
+    Sgr(Sgr<'a>),
+    /// an operating system command like a hyperlink or window title,
+    /// terminated by bel or st
+    Osc(&'a str),
+    /// any other control sequence, like cursor movement or erasing a line
+    Csi(&'a str),
+    /// any other escape sequence
+    Escape(&'a str),
+}
+
+/// a decoded sgr sequence, e.g. `\x1B[1;31m`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sgr<'a> {
+    raw: &'a str,
+}
+
+impl<'a> Sgr<'a> {
+    /// the whole escape sequence
+    pub fn as_str(&self) -> &'a str {
+        self.raw
+    }
+
+    /// the parameters between `\x1B[` and `m`, e.g. `1;31`
+    pub fn params(&self) -> &'a str {
+        &self.raw[2..self.raw.len() - 1]
+    }
+
+    /// whether the sequence ends by resetting every attribute
+    pub fn is_reset(&self) -> bool {
+        last_reset(self.params()) == Some(self.params().len())
+    }
+
+    /// the style in effect after this sequence, when `style` was in effect
+    /// before it
+    ///
+    /// example:
+    /// ```rs
+    /// let style = Sgr::parse("\x1B[1;38;5;208m").unwrap().apply(Style::new());
+    /// assert_eq!(style, Style::new().bold().fg(Color::Ansi256(208)));
+    /// ```
+    pub fn apply(&self, mut style: Style) -> Style {
+        let mut params = self.params().split(';');
+        while let Some(param) = params.next() {
+            let mut parts = param.split(':');
+            let code = match parts.next().unwrap_or_default() {
+                "" => 0,
+                code => match code.parse::<u16>() {
+                    Ok(code) => code,
+                    Err(_) => continue,
+                },
+            };
+            match code {
+                0 => style = Style::new(),
+                // 4:0 turns underline off, 4:1 to 4:5 pick a line shape
+                4 if parts.next().is_some_and(|shape| shape == "0") => {
+                    style.attributes.remove(Attributes::UNDERLINE);
+                }
+                1..=9 => style.attributes.insert(Attributes::from_code(code)),
+                21 => style.attributes.insert(Attributes::UNDERLINE),
+                22 => style.attributes.remove(Attributes::BOLD | Attributes::DIM),
+                23 => style.attributes.remove(Attributes::ITALIC),
+                24 => style.attributes.remove(Attributes::UNDERLINE),
+                25 => style
+                    .attributes
+                    .remove(Attributes::BLINK | Attributes::BLINK_FAST),
+                27 => style.attributes.remove(Attributes::REVERSE),
+                28 => style.attributes.remove(Attributes::HIDDEN),
+                29 => style.attributes.remove(Attributes::STRIKETHROUGH),
+                30..=37 => style.fg = Some(Color::Ansi16(code as u8 - 30)),
+                40..=47 => style.bg = Some(Color::Ansi16(code as u8 - 40)),
+                90..=97 => style.fg = Some(Color::Ansi16(code as u8 - 82)),
+                100..=107 => style.bg = Some(Color::Ansi16(code as u8 - 92)),
+                39 => style.fg = None,
+                49 => style.bg = None,
+                59 => style.underline_color = None,
+                38 | 48 | 58 => {
+                    // either 38;5;n or the colon form 38:5:n
+                    let color = if param.contains(':') {
+                        extended_color(&mut parts, true)
+                    } else {
+                        extended_color(&mut params, false)
+                    };
+                    let slot = match code {
+                        38 => &mut style.fg,
+                        48 => &mut style.bg,
+                        _ => &mut style.underline_color,
+                    };
+                    if let Some(color) = color {
+                        *slot = Some(color);
+                    }
+                }
+                _ => {}
+            }
+        }
+        style
+    }
+
+    /// reads a single sgr sequence, `None` if `s` is anything else
+    pub fn parse(s: &'a str) -> Option<Self> {
+        match tokens(s).next() {
+            Some(Token::Sgr(sgr)) if sgr.raw.len() == s.len() => Some(sgr),
+            _ => None,
+        }
+    }
+}
+
+/// reads the arguments of a 38, 48 or 58 parameter
+fn extended_color<'a>(args: &mut impl Iterator<Item = &'a str>, colon: bool) -> Option<Color> {
+    match args.next()? {
+        "5" => args.next()?.parse().ok().map(Color::Ansi256),
+        "2" if colon => {
+            // 38:2:r:g:b, or 38:2:id:r:g:b with a usually empty color space id
+            let mut values = [None; 4];
+            let mut len = 0;
+            for arg in args.take(4) {
+                values[len] = arg.parse::<u8>().ok();
+                len += 1;
+            }
+            match values[..len] {
+                [_, Some(r), Some(g), Some(b)] | [Some(r), Some(g), Some(b)] => {
+                    Some(Color::Rgb(r, g, b))
+                }
+                _ => None,
+            }
+        }
+        "2" => {
+            let mut channel = || -> Option<u8> { args.next()?.parse().ok() };
+            Some(Color::Rgb(channel()?, channel()?, channel()?))
+        }
+        _ => None,
+    }
+}
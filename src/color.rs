@@ -0,0 +1,453 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::sync::OnceLock;
+
+use crate::css::CSS_COLORS;
+use crate::space;
+use crate::term::{self, ColorLevel};
+
+/// a terminal color
+///
+/// colors can be parsed from strings, built from rgb tuples or packed
+/// `0xRRGGBB` integers, and passed to `text_color` / `bg_color`
+///
+/// example:
+/// ```rs
+/// let c: Color = "#f40420".parse()?;
+/// println!("{}", "hi!".text_color(c));
+/// println!("{}", "hi!".bg_color(Color::from((30, 64, 175))));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Color {
+    /// 24-bit color
+    Rgb(u8, u8, u8),
+    /// one of the 16 basic terminal colors (0-7 normal, 8-15 bright)
+    Ansi16(u8),
+    /// one of the 256 xterm palette colors
+    Ansi256(u8),
+    /// a color from the crate's named palette
+    Named(NamedColor),
+    /// the terminal's own default color, which unlike leaving a color unset
+    /// also undoes one set around it
+    Default,
+}
+
+/// a color referenced by its name, e.g. `red`, `cyan_bright`, `blue_500`
+/// or `rebeccapurple`
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NamedColor(&'static NamedEntry);
+
+#[derive(PartialEq, Eq, Hash)]
+pub(crate) struct NamedEntry {
+    pub(crate) name: &'static str,
+    pub(crate) rgb: (u8, u8, u8),
+    pub(crate) ansi: Option<u8>,
+}
+
+const fn entry(name: &'static str, rgb: (u8, u8, u8), ansi: u8) -> NamedEntry {
+    NamedEntry {
+        name,
+        rgb,
+        ansi: Some(ansi),
+    }
+}
+
+// default and bright colors, in ansi order
+static DEFAULT_COLORS: [NamedEntry; 16] = [
+    entry("black", (0, 0, 0), 0),
+    entry("red", (255, 0, 0), 1),
+    entry("green", (0, 255, 0), 2),
+    entry("yellow", (255, 255, 0), 3),
+    entry("blue", (0, 0, 255), 4),
+    entry("magenta", (255, 0, 255), 5),
+    entry("cyan", (0, 255, 255), 6),
+    entry("white", (255, 255, 255), 7),
+    entry("gray", (128, 128, 128), 8),
+    entry("red_bright", (255, 85, 85), 9),
+    entry("green_bright", (85, 255, 85), 10),
+    entry("yellow_bright", (255, 255, 85), 11),
+    entry("blue_bright", (85, 85, 255), 12),
+    entry("magenta_bright", (255, 85, 255), 13),
+    entry("cyan_bright", (85, 255, 255), 14),
+    entry("white_bright", (255, 255, 255), 15),
+];
+
+const fn accent(name: &'static str, rgb: (u8, u8, u8)) -> NamedEntry {
+    NamedEntry {
+        name,
+        rgb,
+        ansi: None,
+    }
+}
+
+/// whether `method` is `text_` followed by `name`
+const fn is_text_method(method: &[u8], name: &[u8]) -> bool {
+    const PREFIX: &[u8] = b"text_";
+    if method.len() != PREFIX.len() + name.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < method.len() {
+        let expected = match i < PREFIX.len() {
+            true => PREFIX[i],
+            false => name[i - PREFIX.len()],
+        };
+        if method[i] != expected {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+// accent colors, matching the `text_*_50` .. `text_*_950` methods
+static ACCENT_COLORS: [NamedEntry; 77] = [
+    accent("red_50", (254, 242, 242)),
+    accent("red_100", (254, 226, 226)),
+    accent("red_200", (254, 202, 202)),
+    accent("red_300", (252, 165, 165)),
+    accent("red_400", (248, 113, 113)),
+    accent("red_500", (239, 68, 68)),
+    accent("red_600", (220, 38, 38)),
+    accent("red_700", (185, 28, 28)),
+    accent("red_800", (153, 27, 27)),
+    accent("red_900", (127, 29, 29)),
+    accent("red_950", (69, 10, 10)),
+    accent("yellow_50", (254, 252, 232)),
+    accent("yellow_100", (254, 249, 195)),
+    accent("yellow_200", (254, 240, 138)),
+    accent("yellow_300", (253, 224, 71)),
+    accent("yellow_400", (250, 204, 21)),
+    accent("yellow_500", (234, 179, 8)),
+    accent("yellow_600", (202, 138, 4)),
+    accent("yellow_700", (161, 98, 7)),
+    accent("yellow_800", (133, 77, 14)),
+    accent("yellow_900", (113, 63, 18)),
+    accent("yellow_950", (66, 32, 6)),
+    accent("green_50", (240, 253, 244)),
+    accent("green_100", (220, 252, 231)),
+    accent("green_200", (187, 247, 208)),
+    accent("green_300", (134, 239, 172)),
+    accent("green_400", (74, 222, 128)),
+    accent("green_500", (34, 197, 94)),
+    accent("green_600", (22, 163, 74)),
+    accent("green_700", (21, 128, 61)),
+    accent("green_800", (22, 101, 52)),
+    accent("green_900", (20, 83, 45)),
+    accent("green_950", (5, 46, 22)),
+    accent("blue_50", (239, 246, 255)),
+    accent("blue_100", (219, 234, 254)),
+    accent("blue_200", (191, 219, 254)),
+    accent("blue_300", (147, 197, 253)),
+    accent("blue_400", (96, 165, 250)),
+    accent("blue_500", (59, 130, 246)),
+    accent("blue_600", (37, 99, 235)),
+    accent("blue_700", (29, 78, 216)),
+    accent("blue_800", (30, 64, 175)),
+    accent("blue_900", (30, 58, 138)),
+    accent("blue_950", (23, 37, 84)),
+    accent("purple_50", (250, 245, 255)),
+    accent("purple_100", (243, 232, 255)),
+    accent("purple_200", (233, 213, 255)),
+    accent("purple_300", (216, 180, 254)),
+    accent("purple_400", (192, 132, 252)),
+    accent("purple_500", (168, 85, 247)),
+    accent("purple_600", (147, 51, 234)),
+    accent("purple_700", (126, 34, 206)),
+    accent("purple_800", (107, 33, 168)),
+    accent("purple_900", (88, 28, 135)),
+    accent("purple_950", (59, 7, 100)),
+    accent("pink_50", (253, 242, 248)),
+    accent("pink_100", (252, 231, 243)),
+    accent("pink_200", (251, 207, 232)),
+    accent("pink_300", (249, 168, 212)),
+    accent("pink_400", (244, 114, 182)),
+    accent("pink_500", (236, 72, 153)),
+    accent("pink_600", (219, 39, 119)),
+    accent("pink_700", (190, 24, 93)),
+    accent("pink_800", (157, 23, 77)),
+    accent("pink_900", (131, 24, 67)),
+    accent("pink_950", (80, 7, 36)),
+    accent("black_50", (249, 250, 251)),
+    accent("black_100", (243, 244, 246)),
+    accent("black_200", (229, 231, 235)),
+    accent("black_300", (209, 213, 219)),
+    accent("black_400", (156, 163, 175)),
+    accent("black_500", (107, 114, 128)),
+    accent("black_600", (75, 85, 99)),
+    accent("black_700", (55, 65, 81)),
+    accent("black_800", (31, 41, 55)),
+    accent("black_900", (17, 24, 39)),
+    accent("black_950", (3, 7, 18)),
+];
+
+impl NamedColor {
+    /// the accent color a `text_*` method is named after, looked up when
+    /// the crate is built so a method without a color fails to compile
+    pub(crate) const fn accent(method: &str) -> Self {
+        let method = method.as_bytes();
+        let mut i = 0;
+        while i < ACCENT_COLORS.len() {
+            if is_text_method(method, ACCENT_COLORS[i].name.as_bytes()) {
+                return NamedColor(&ACCENT_COLORS[i]);
+            }
+            i += 1;
+        }
+        panic!("no accent color for this method")
+    }
+
+    /// looks a color up by name, `text_` and `bg_` prefixes are ignored
+    /// and the lookup is ascii case-insensitive
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name
+            .strip_prefix("text_")
+            .or_else(|| name.strip_prefix("bg_"))
+            .unwrap_or(name);
+
+        Self::all().find(|c| c.name().eq_ignore_ascii_case(name))
+    }
+
+    /// looks a color up by its rgb value, default colors win over accents
+    pub fn from_rgb(rgb: (u8, u8, u8)) -> Option<Self> {
+        Self::all().find(|c| c.to_rgb() == rgb)
+    }
+
+    /// every named color, default and bright colors first, then accents,
+    /// then css named colors
+    pub fn all() -> impl Iterator<Item = NamedColor> {
+        DEFAULT_COLORS
+            .iter()
+            .chain(ACCENT_COLORS.iter())
+            .chain(CSS_COLORS.iter())
+            .map(NamedColor)
+    }
+
+    /// name of the color, without any `text_` / `bg_` prefix
+    pub fn name(self) -> &'static str {
+        self.0.name
+    }
+
+    /// rgb value of the color
+    pub fn to_rgb(self) -> (u8, u8, u8) {
+        self.0.rgb
+    }
+
+    /// basic terminal color index, if the name maps to one
+    pub fn ansi(self) -> Option<u8> {
+        self.0.ansi
+    }
+}
+
+impl Color {
+    /// converts the color into rgb components
+    ///
+    /// basic and palette colors use the crate's reference values, the
+    /// actual terminal may be themed differently, and its default color is
+    /// taken as the light grey most terminals draw text in
+    pub fn to_rgb(self) -> (u8, u8, u8) {
+        match self {
+            Color::Default => (192, 192, 192),
+            Color::Rgb(r, g, b) => (r, g, b),
+            Color::Ansi16(n) => DEFAULT_COLORS[(n & 15) as usize].rgb,
+            Color::Ansi256(n) => ansi256_to_rgb(n),
+            Color::Named(named) => named.to_rgb(),
+        }
+    }
+
+    /// name of the color in the crate's palette, if it has one
+    ///
+    /// rgb colors are looked up by value, so `Color::from(0x3b82f6)`
+    /// reports `blue_500`
+    pub fn name(self) -> Option<&'static str> {
+        match self {
+            Color::Named(named) => Some(named.name()),
+            Color::Default => Some("default"),
+            Color::Ansi16(n) => Some(DEFAULT_COLORS[(n & 15) as usize].name),
+            Color::Ansi256(n) if n < 16 => Some(DEFAULT_COLORS[n as usize].name),
+            _ => NamedColor::from_rgb(self.to_rgb()).map(NamedColor::name),
+        }
+    }
+
+    /// formats the color as `#rrggbb`
+    pub fn to_hex(self) -> String {
+        let (r, g, b) = self.to_rgb();
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    }
+
+    /// closest color a terminal with the given level can show
+    ///
+    /// basic colors are kept as they are, everything else is matched to
+    /// the palette by distance in oklab, so the result looks as close as
+    /// possible rather than just having the closest channel values
+    ///
+    /// example:
+    /// ```rs
+    /// let c = Color::from(0x3b82f6).downsample(ColorLevel::Ansi256);
+    /// assert_eq!(c, Color::Ansi256(33));
+    /// ```
+    pub fn downsample(self, level: ColorLevel) -> Color {
+        match (self, level) {
+            (_, ColorLevel::TrueColor) | (Color::Ansi16(_) | Color::Default, _) => self,
+            (Color::Named(named), _) if named.ansi().is_some() => self,
+            (Color::Ansi256(_), ColorLevel::Ansi256) => self,
+            (Color::Ansi256(n), ColorLevel::Ansi16) if n < 16 => Color::Ansi16(n),
+            (_, ColorLevel::Ansi256) => Color::Ansi256(nearest(self.to_rgb(), 16..=255)),
+            (_, ColorLevel::Ansi16) => Color::Ansi16(nearest(self.to_rgb(), 0..=15)),
+        }
+    }
+
+    /// sgr parameters that set this color as foreground
+    pub(crate) fn fg_params(self) -> String {
+        let mut params = String::new();
+        let _ = self.write_params(Layer::Fg, &mut params);
+        params
+    }
+
+    /// sgr parameters that set this color as background
+    pub(crate) fn bg_params(self) -> String {
+        let mut params = String::new();
+        let _ = self.write_params(Layer::Bg, &mut params);
+        params
+    }
+
+    /// writes the sgr parameters for this color at the current color level
+    pub(crate) fn write_params(self, layer: Layer, out: &mut impl fmt::Write) -> fmt::Result {
+        let base = match layer {
+            Layer::Fg => 38,
+            Layer::Bg => 48,
+            Layer::Underline => 58,
+        };
+        let basic = |n: u8, out: &mut dyn fmt::Write| match layer {
+            // there are no basic codes for underline colors
+            Layer::Underline => write!(out, "{};5;{}", base, n & 15),
+            _ => write!(out, "{}", ansi16_code(n, layer == Layer::Bg)),
+        };
+        match self.downsample(term::color_level()) {
+            Color::Ansi16(n) => basic(n, out),
+            Color::Ansi256(n) => write!(out, "{};5;{}", base, n),
+            Color::Named(named) => match named.ansi() {
+                Some(n) => basic(n, out),
+                None => {
+                    let (r, g, b) = named.to_rgb();
+                    write!(out, "{};2;{};{};{}", base, r, g, b)
+                }
+            },
+            Color::Rgb(r, g, b) => write!(out, "{};2;{};{};{}", base, r, g, b),
+            // 39, 49 and 59 go back to the terminal's own colors
+            Color::Default => write!(out, "{}", base + 1),
+        }
+    }
+}
+
+/// what an emitted color applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Layer {
+    Fg,
+    Bg,
+    Underline,
+}
+
+fn ansi16_code(n: u8, bg: bool) -> u8 {
+    let n = n & 15;
+    let offset = if bg { 10 } else { 0 };
+    if n < 8 {
+        30 + n + offset
+    } else {
+        90 + (n - 8) + offset
+    }
+}
+
+/// index of the palette color in `range` closest to `rgb`
+fn nearest(rgb: (u8, u8, u8), range: RangeInclusive<u8>) -> u8 {
+    let lab = to_oklab(rgb);
+    let palette = palette_lab();
+    let start = *range.start();
+    range
+        .min_by(|&a, &b| {
+            let a = distance(lab, palette[a as usize]);
+            a.total_cmp(&distance(lab, palette[b as usize]))
+        })
+        .unwrap_or(start)
+}
+
+fn to_oklab(rgb: (u8, u8, u8)) -> [f32; 3] {
+    space::linear_to_oklab(space::from_bytes(rgb).map(space::srgb_to_linear))
+}
+
+fn distance(a: [f32; 3], b: [f32; 3]) -> f32 {
+    (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)
+}
+
+/// the 256 color palette in oklab, computed once
+fn palette_lab() -> &'static [[f32; 3]; 256] {
+    static PALETTE: OnceLock<[[f32; 3]; 256]> = OnceLock::new();
+    PALETTE.get_or_init(|| {
+        let mut palette = [[0.0; 3]; 256];
+        for (i, lab) in palette.iter_mut().enumerate() {
+            *lab = to_oklab(ansi256_to_rgb(i as u8));
+        }
+        palette
+    })
+}
+
+fn ansi256_to_rgb(n: u8) -> (u8, u8, u8) {
+    match n {
+        0..=15 => DEFAULT_COLORS[n as usize].rgb,
+        16..=231 => {
+            const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+            let i = n - 16;
+            (
+                LEVELS[(i / 36) as usize],
+                LEVELS[(i / 6 % 6) as usize],
+                LEVELS[(i % 6) as usize],
+            )
+        }
+        _ => {
+            let v = 8 + (n - 232) * 10;
+            (v, v, v)
+        }
+    }
+}
+
+impl From<(u8, u8, u8)> for Color {
+    fn from((r, g, b): (u8, u8, u8)) -> Self {
+        Color::Rgb(r, g, b)
+    }
+}
+
+impl From<u32> for Color {
+    /// builds a color from a packed `0xRRGGBB` value, the top byte is ignored
+    fn from(v: u32) -> Self {
+        Color::Rgb((v >> 16) as u8, (v >> 8) as u8, v as u8)
+    }
+}
+
+impl From<NamedColor> for Color {
+    fn from(named: NamedColor) -> Self {
+        Color::Named(named)
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Color::Rgb(..) => f.write_str(&self.to_hex()),
+            Color::Ansi16(n) => write!(f, "ansi16({})", n),
+            Color::Ansi256(n) => write!(f, "ansi256({})", n),
+            Color::Named(named) => f.write_str(named.name()),
+            Color::Default => f.write_str("default"),
+        }
+    }
+}
+
+impl fmt::Debug for NamedColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("NamedColor").field(&self.name()).finish()
+    }
+}
+
+impl fmt::Display for NamedColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
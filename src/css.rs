@@ -0,0 +1,154 @@
+use crate::color::NamedEntry;
+
+const fn css(name: &'static str, rgb: (u8, u8, u8)) -> NamedEntry {
+    NamedEntry {
+        name,
+        rgb,
+        ansi: None,
+    }
+}
+
+// css named colors, names shared with the default colors (black, red,
+// green, yellow, blue, magenta, cyan, white, gray) are left out because
+// the terminal palette takes precedence for them
+pub(crate) static CSS_COLORS: [NamedEntry; 139] = [
+    css("aliceblue", (240, 248, 255)),
+    css("antiquewhite", (250, 235, 215)),
+    css("aqua", (0, 255, 255)),
+    css("aquamarine", (127, 255, 212)),
+    css("azure", (240, 255, 255)),
+    css("beige", (245, 245, 220)),
+    css("bisque", (255, 228, 196)),
+    css("blanchedalmond", (255, 235, 205)),
+    css("blueviolet", (138, 43, 226)),
+    css("brown", (165, 42, 42)),
+    css("burlywood", (222, 184, 135)),
+    css("cadetblue", (95, 158, 160)),
+    css("chartreuse", (127, 255, 0)),
+    css("chocolate", (210, 105, 30)),
+    css("coral", (255, 127, 80)),
+    css("cornflowerblue", (100, 149, 237)),
+    css("cornsilk", (255, 248, 220)),
+    css("crimson", (220, 20, 60)),
+    css("darkblue", (0, 0, 139)),
+    css("darkcyan", (0, 139, 139)),
+    css("darkgoldenrod", (184, 134, 11)),
+    css("darkgray", (169, 169, 169)),
+    css("darkgreen", (0, 100, 0)),
+    css("darkgrey", (169, 169, 169)),
+    css("darkkhaki", (189, 183, 107)),
+    css("darkmagenta", (139, 0, 139)),
+    css("darkolivegreen", (85, 107, 47)),
+    css("darkorange", (255, 140, 0)),
+    css("darkorchid", (153, 50, 204)),
+    css("darkred", (139, 0, 0)),
+    css("darksalmon", (233, 150, 122)),
+    css("darkseagreen", (143, 188, 143)),
+    css("darkslateblue", (72, 61, 139)),
+    css("darkslategray", (47, 79, 79)),
+    css("darkslategrey", (47, 79, 79)),
+    css("darkturquoise", (0, 206, 209)),
+    css("darkviolet", (148, 0, 211)),
+    css("deeppink", (255, 20, 147)),
+    css("deepskyblue", (0, 191, 255)),
+    css("dimgray", (105, 105, 105)),
+    css("dimgrey", (105, 105, 105)),
+    css("dodgerblue", (30, 144, 255)),
+    css("firebrick", (178, 34, 34)),
+    css("floralwhite", (255, 250, 240)),
+    css("forestgreen", (34, 139, 34)),
+    css("fuchsia", (255, 0, 255)),
+    css("gainsboro", (220, 220, 220)),
+    css("ghostwhite", (248, 248, 255)),
+    css("gold", (255, 215, 0)),
+    css("goldenrod", (218, 165, 32)),
+    css("greenyellow", (173, 255, 47)),
+    css("grey", (128, 128, 128)),
+    css("honeydew", (240, 255, 240)),
+    css("hotpink", (255, 105, 180)),
+    css("indianred", (205, 92, 92)),
+    css("indigo", (75, 0, 130)),
+    css("ivory", (255, 255, 240)),
+    css("khaki", (240, 230, 140)),
+    css("lavender", (230, 230, 250)),
+    css("lavenderblush", (255, 240, 245)),
+    css("lawngreen", (124, 252, 0)),
+    css("lemonchiffon", (255, 250, 205)),
+    css("lightblue", (173, 216, 230)),
+    css("lightcoral", (240, 128, 128)),
+    css("lightcyan", (224, 255, 255)),
+    css("lightgoldenrodyellow", (250, 250, 210)),
+    css("lightgray", (211, 211, 211)),
+    css("lightgreen", (144, 238, 144)),
+    css("lightgrey", (211, 211, 211)),
+    css("lightpink", (255, 182, 193)),
+    css("lightsalmon", (255, 160, 122)),
+    css("lightseagreen", (32, 178, 170)),
+    css("lightskyblue", (135, 206, 250)),
+    css("lightslategray", (119, 136, 153)),
+    css("lightslategrey", (119, 136, 153)),
+    css("lightsteelblue", (176, 196, 222)),
+    css("lightyellow", (255, 255, 224)),
+    css("lime", (0, 255, 0)),
+    css("limegreen", (50, 205, 50)),
+    css("linen", (250, 240, 230)),
+    css("maroon", (128, 0, 0)),
+    css("mediumaquamarine", (102, 205, 170)),
+    css("mediumblue", (0, 0, 205)),
+    css("mediumorchid", (186, 85, 211)),
+    css("mediumpurple", (147, 112, 219)),
+    css("mediumseagreen", (60, 179, 113)),
+    css("mediumslateblue", (123, 104, 238)),
+    css("mediumspringgreen", (0, 250, 154)),
+    css("mediumturquoise", (72, 209, 204)),
+    css("mediumvioletred", (199, 21, 133)),
+    css("midnightblue", (25, 25, 112)),
+    css("mintcream", (245, 255, 250)),
+    css("mistyrose", (255, 228, 225)),
+    css("moccasin", (255, 228, 181)),
+    css("navajowhite", (255, 222, 173)),
+    css("navy", (0, 0, 128)),
+    css("oldlace", (253, 245, 230)),
+    css("olive", (128, 128, 0)),
+    css("olivedrab", (107, 142, 35)),
+    css("orange", (255, 165, 0)),
+    css("orangered", (255, 69, 0)),
+    css("orchid", (218, 112, 214)),
+    css("palegoldenrod", (238, 232, 170)),
+    css("palegreen", (152, 251, 152)),
+    css("paleturquoise", (175, 238, 238)),
+    css("palevioletred", (219, 112, 147)),
+    css("papayawhip", (255, 239, 213)),
+    css("peachpuff", (255, 218, 185)),
+    css("peru", (205, 133, 63)),
+    css("pink", (255, 192, 203)),
+    css("plum", (221, 160, 221)),
+    css("powderblue", (176, 224, 230)),
+    css("purple", (128, 0, 128)),
+    css("rebeccapurple", (102, 51, 153)),
+    css("rosybrown", (188, 143, 143)),
+    css("royalblue", (65, 105, 225)),
+    css("saddlebrown", (139, 69, 19)),
+    css("salmon", (250, 128, 114)),
+    css("sandybrown", (244, 164, 96)),
+    css("seagreen", (46, 139, 87)),
+    css("seashell", (255, 245, 238)),
+    css("sienna", (160, 82, 45)),
+    css("silver", (192, 192, 192)),
+    css("skyblue", (135, 206, 235)),
+    css("slateblue", (106, 90, 205)),
+    css("slategray", (112, 128, 144)),
+    css("slategrey", (112, 128, 144)),
+    css("snow", (255, 250, 250)),
+    css("springgreen", (0, 255, 127)),
+    css("steelblue", (70, 130, 180)),
+    css("tan", (210, 180, 140)),
+    css("teal", (0, 128, 128)),
+    css("thistle", (216, 191, 216)),
+    css("tomato", (255, 99, 71)),
+    css("turquoise", (64, 224, 208)),
+    css("violet", (238, 130, 238)),
+    css("wheat", (245, 222, 179)),
+    css("whitesmoke", (245, 245, 245)),
+    css("yellowgreen", (154, 205, 50)),
+];
@@ -0,0 +1,290 @@
+use crate::ansi::{self, Segment};
+use crate::color::Color;
+use crate::gradient::GradientSpace;
+use crate::style::Style;
+use crate::term;
+use crate::width::{grapheme_width, graphemes};
+
+/// which end of the text fades into the background
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FadeDirection {
+    /// the text comes out of the background, faint at the start
+    #[default]
+    In,
+    /// the text goes into the background, faint at the end
+    Out,
+}
+
+/// settings for `Colors::fade`
+///
+/// the text's own foreground color is blended towards the background along
+/// its whole length, lines one after the other. a direction converts into
+/// options with the defaults, so both `text.fade(FadeDirection::Out)` and
+/// `text.fade(FadeOptions::new(FadeDirection::Out).by_word(false))` work
+///
+/// example:
+/// ```rs
+/// let hint = "press q to quit".fade(
+///     FadeOptions::new(FadeDirection::Out)
+///         .color(Color::from(0x93c5fd))
+///         .background(Color::from(0x1e1e2e)),
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FadeOptions {
+    direction: FadeDirection,
+    color: Option<Color>,
+    background: Color,
+    strength: f32,
+    by_word: bool,
+}
+
+impl FadeOptions {
+    /// fades towards black, a word at a time
+    pub fn new(direction: FadeDirection) -> Self {
+        FadeOptions {
+            direction,
+            color: None,
+            background: Color::Rgb(0, 0, 0),
+            strength: 0.85,
+            by_word: true,
+        }
+    }
+
+    /// fades this color instead of the text's own, which is also used where
+    /// the text has no color of its own
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// the color faded towards, black by default since the terminal's own
+    /// background can't be asked for
+    pub fn background(mut self, color: Color) -> Self {
+        self.background = color;
+        self
+    }
+
+    /// how close the faint end gets to the background, from `0.0` to `1.0`,
+    /// `0.85` by default so it stays readable
+    pub fn strength(mut self, strength: f32) -> Self {
+        self.strength = strength.clamp(0.0, 1.0);
+        self
+    }
+
+    /// whether every word gets a single color, on by default, otherwise
+    /// every character gets its own
+    pub fn by_word(mut self, by_word: bool) -> Self {
+        self.by_word = by_word;
+        self
+    }
+}
+
+impl From<FadeDirection> for FadeOptions {
+    fn from(direction: FadeDirection) -> Self {
+        FadeOptions::new(direction)
+    }
+}
+
+/// the foreground of text without a color of its own, the usual light grey
+/// of terminal text, `Ansi16(7)` would be taken as pure white
+const DEFAULT_FG: Color = Color::Rgb(192, 192, 192);
+
+#[derive(Clone, Copy)]
+enum Item<'a> {
+    Escape(&'a str),
+    Grapheme(&'a str, usize),
+}
+
+/// blends every visible character of `text` towards the background by how
+/// far along the text it is
+///
+/// escapes in the text are kept and its own colors are faded, whitespace is
+/// left alone, and every line still styled at its end is closed
+pub(crate) fn fade(text: &str, options: &FadeOptions) -> String {
+    if text.is_empty() || !term::colors_enabled() {
+        return text.to_string();
+    }
+
+    let source = ansi::lines(text);
+    let mut lines = Vec::new();
+    for line in &source {
+        let mut items = Vec::new();
+        for segment in ansi::segments(line) {
+            match segment {
+                Segment::Escape(e) => items.push(Item::Escape(e)),
+                Segment::Text(t) => {
+                    items.extend(graphemes(t).map(|g| Item::Grapheme(g, grapheme_width(g))));
+                }
+            }
+        }
+        lines.push(items);
+    }
+
+    // positions run through all lines in reading order, a cell sits at its
+    // middle and a word at the middle of the word
+    let total: usize = lines
+        .iter()
+        .flatten()
+        .map(|item| match item {
+            Item::Grapheme(_, w) => *w,
+            Item::Escape(_) => 0,
+        })
+        .sum();
+    let total = total.max(1) as f32;
+    let mut column = 0;
+
+    let mut result = Vec::new();
+    for items in &lines {
+        let mut line = String::new();
+        // the style of the text itself, its foreground is what gets faded
+        let mut active = Style::new();
+        // the color last set, escapes from the text may have changed it since
+        let mut last = None;
+        let mut i = 0;
+        while i < items.len() {
+            let (g, w) = match items[i] {
+                Item::Escape(e) => {
+                    ansi::track_sgr(&mut active, e);
+                    line.push_str(e);
+                    last = None;
+                    i += 1;
+                    continue;
+                }
+                Item::Grapheme(g, w) => (g, w),
+            };
+            if g.starts_with(char::is_whitespace) {
+                line.push_str(g);
+                column += w;
+                i += 1;
+                continue;
+            }
+
+            let width = match options.by_word {
+                true => word_width(&items[i..]),
+                false => w,
+            };
+            let position = (column as f32 + width as f32 / 2.0) / total;
+            let fade = match options.direction {
+                FadeDirection::In => 1.0 - position,
+                FadeDirection::Out => position,
+            };
+
+            // the word, with escapes inside it, in one go
+            let end = match options.by_word {
+                true => i + word_len(&items[i..]),
+                false => i + 1,
+            };
+            for item in &items[i..end] {
+                match *item {
+                    Item::Escape(e) => {
+                        ansi::track_sgr(&mut active, e);
+                        line.push_str(e);
+                        last = None;
+                    }
+                    Item::Grapheme(g, w) => {
+                        let own = options.color.or(active.fg).unwrap_or(DEFAULT_FG);
+                        let color = GradientSpace::Oklab.mix(
+                            own,
+                            options.background,
+                            fade * options.strength,
+                        );
+                        let prefix = Style::new().fg(color).prefix();
+                        if last.as_ref() != Some(&prefix) {
+                            line.push_str(&prefix);
+                            last = Some(prefix);
+                        }
+                        line.push_str(g);
+                        column += w;
+                    }
+                }
+            }
+            i = end;
+        }
+        // text that already ends with a reset isn't closed a second time
+        if last.is_some() || !active.is_plain() {
+            line.push_str("\x1B[0m");
+        }
+        result.push(line);
+    }
+    result.join("\n")
+}
+
+/// items up to the end of the word `items` starts with
+fn word_len(items: &[Item<'_>]) -> usize {
+    items
+        .iter()
+        .position(|item| matches!(item, Item::Grapheme(g, _) if g.starts_with(char::is_whitespace)))
+        .unwrap_or(items.len())
+}
+
+/// columns of the word `items` starts with
+fn word_width(items: &[Item<'_>]) -> usize {
+    items[..word_len(items)]
+        .iter()
+        .map(|item| match item {
+            Item::Grapheme(_, w) => *w,
+            Item::Escape(_) => 0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::term::colored;
+
+    fn prefix(color: Color) -> String {
+        Style::new().fg(color).prefix()
+    }
+
+    #[test]
+    fn every_character_gets_its_own_color() {
+        let (color, background) = (Color::Rgb(250, 200, 100), Color::Rgb(0, 0, 40));
+        let options = FadeOptions::new(FadeDirection::Out)
+            .color(color)
+            .background(background)
+            .strength(0.5)
+            .by_word(false);
+        let faded = colored(|| fade("abcd", &options));
+
+        let mut expected = String::new();
+        for (i, c) in "abcd".chars().enumerate() {
+            let position = (i as f32 + 0.5) / 4.0;
+            let mixed = GradientSpace::Oklab.mix(color, background, position * 0.5);
+            expected.push_str(&colored(|| prefix(mixed)));
+            expected.push(c);
+        }
+        expected.push_str("\x1B[0m");
+        assert_eq!(faded, expected);
+    }
+
+    #[test]
+    fn words_share_a_color() {
+        let count = |by_word| {
+            let options = FadeOptions::new(FadeDirection::In).by_word(by_word);
+            colored(|| fade("ab cd", &options))
+                .matches("\x1B[38;2;")
+                .count()
+        };
+        assert_eq!(count(true), 2);
+        assert_eq!(count(false), 4);
+    }
+
+    #[test]
+    fn existing_styles_are_kept() {
+        let options = FadeOptions::new(FadeDirection::In).strength(0.0);
+        let faded = colored(|| fade("\x1B[1;38;2;10;20;30mab\x1B[0m", &options));
+        let own = colored(|| prefix(Color::Rgb(10, 20, 30)));
+        assert_eq!(faded, format!("\x1B[1;38;2;10;20;30m{}ab\x1B[0m", own));
+    }
+
+    #[test]
+    fn styled_text_is_closed_once() {
+        let faded = colored(|| fade("\x1B[31mab\x1B[0m", &FadeDirection::Out.into()));
+        assert!(faded.ends_with("ab\x1B[0m"), "{:?}", faded);
+        assert!(!faded.ends_with("\x1B[0m\x1B[0m"), "{:?}", faded);
+        let faded = colored(|| fade("ab", &FadeDirection::Out.into()));
+        assert!(faded.ends_with("ab\x1B[0m"), "{:?}", faded);
+    }
+}
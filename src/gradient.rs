@@ -0,0 +1,722 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::ansi::{self, Segment};
+use crate::color::Color;
+use crate::parse::ColorParseError;
+use crate::space;
+use crate::term;
+use crate::width::{grapheme_width, graphemes};
+
+/// color space gradients are interpolated in
+///
+/// `Srgb` blends the raw channel values, which is what terminals and most
+/// tools do but tends to produce dark, muddy midpoints between
+/// complementary colors, `Oklab` and `Oklch` keep perceived lightness even
+///
+/// example:
+/// ```rs
+/// let s = "rainbow".text_gradient_in(&["red", "blue"], GradientSpace::Oklab);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GradientSpace {
+    /// gamma encoded srgb, the classic behaviour
+    #[default]
+    Srgb,
+    /// linear light srgb, physically correct blending
+    LinearRgb,
+    /// oklab, perceptually uniform lightness and chroma
+    Oklab,
+    /// oklch, oklab in polar form, hue travels around the color wheel
+    Oklch(HueArc),
+    /// hsl, hue travels around the color wheel
+    Hsl(HueArc),
+}
+
+/// which way around the color wheel a hue is interpolated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HueArc {
+    /// the shorter arc, at most 180 degrees
+    #[default]
+    Shorter,
+    /// the longer arc, at least 180 degrees
+    Longer,
+}
+
+impl GradientSpace {
+    /// blends `from` towards `to`, `t` is clamped to `0.0..=1.0`
+    pub fn mix(self, from: Color, to: Color, t: f32) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        let a = space::from_bytes(from.to_rgb());
+        let b = space::from_bytes(to.to_rgb());
+
+        let rgb = match self {
+            GradientSpace::Srgb => lerp3(a, b, t),
+            GradientSpace::LinearRgb => {
+                let a = a.map(space::srgb_to_linear);
+                let b = b.map(space::srgb_to_linear);
+                lerp3(a, b, t).map(space::linear_to_srgb)
+            }
+            GradientSpace::Oklab => {
+                let a = space::linear_to_oklab(a.map(space::srgb_to_linear));
+                let b = space::linear_to_oklab(b.map(space::srgb_to_linear));
+                space::oklab_to_linear(lerp3(a, b, t)).map(space::linear_to_srgb)
+            }
+            GradientSpace::Oklch(arc) => {
+                let a = space::rect_to_polar(space::linear_to_oklab(a.map(space::srgb_to_linear)));
+                let b = space::rect_to_polar(space::linear_to_oklab(b.map(space::srgb_to_linear)));
+                let [l, c, h] = lerp_polar(a, b, t, arc);
+                space::oklab_to_linear(space::polar_to_rect(l, c, h)).map(space::linear_to_srgb)
+            }
+            GradientSpace::Hsl(arc) => {
+                // hsl keeps hue first, rotate it into the same layout as lch
+                let [ha, sa, la] = space::rgb_to_hsl(a);
+                let [hb, sb, lb] = space::rgb_to_hsl(b);
+                let [l, s, h] = lerp_polar([la, sa, ha], [lb, sb, hb], t, arc);
+                space::hsl_to_rgb(h, s, l)
+            }
+        };
+
+        let (r, g, b) = space::to_bytes(rgb);
+        Color::Rgb(r, g, b)
+    }
+}
+
+fn lerp3(a: [f32; 3], b: [f32; 3], t: f32) -> [f32; 3] {
+    [
+        a[0] + (b[0] - a[0]) * t,
+        a[1] + (b[1] - a[1]) * t,
+        a[2] + (b[2] - a[2]) * t,
+    ]
+}
+
+/// interpolates `[lightness, chroma, hue]` triples, a `NaN` hue (a grey)
+/// takes the other color's hue so greys don't drag the gradient through red
+fn lerp_polar(a: [f32; 3], b: [f32; 3], t: f32, arc: HueArc) -> [f32; 3] {
+    let (ha, hb) = match (a[2].is_nan(), b[2].is_nan()) {
+        (true, true) => (0.0, 0.0),
+        (true, false) => (b[2], b[2]),
+        (false, true) => (a[2], a[2]),
+        (false, false) => (a[2], a[2] + hue_delta(a[2], b[2], arc)),
+    };
+
+    [
+        a[0] + (b[0] - a[0]) * t,
+        a[1] + (b[1] - a[1]) * t,
+        (ha + (hb - ha) * t).rem_euclid(360.0),
+    ]
+}
+
+fn hue_delta(from: f32, to: f32, arc: HueArc) -> f32 {
+    let d = (to - from).rem_euclid(360.0);
+    match arc {
+        HueArc::Shorter if d > 180.0 => d - 360.0,
+        HueArc::Longer if d > 0.0 && d < 180.0 => d - 360.0,
+        _ => d,
+    }
+}
+
+/// a reusable gradient with stops at explicit positions
+///
+/// stops are kept sorted by position, positions are clamped to
+/// `0.0..=1.0`, and colors between stops are interpolated in the
+/// gradient's [`GradientSpace`]
+///
+/// example:
+/// ```rs
+/// let g = Gradient::new()
+///     .stop(0.0, Color::from(0xff7e5f))
+///     .stop(0.3, "gold".parse()?)
+///     .stop(1.0, "blue_500".parse()?)
+///     .space(GradientSpace::Oklab)
+///     .easing(Easing::EaseInOut);
+///
+/// println!("{}", "sunset".text_gradient_with(&g));
+/// let middle = g.sample(0.5);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Gradient {
+    stops: Vec<(f32, Color)>,
+    space: GradientSpace,
+    easing: Easing,
+    spread: Spread,
+    layout: GradientLayout,
+}
+
+/// error returned when a step of a gradient isn't a valid color
+///
+/// the offsets of the inner error point into the failing step
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GradientParseError {
+    step: usize,
+    error: ColorParseError,
+}
+
+impl GradientParseError {
+    /// index of the step that failed, counting from 0
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    /// why the step isn't a valid color
+    pub fn error(&self) -> &ColorParseError {
+        &self.error
+    }
+}
+
+impl fmt::Display for GradientParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "step {}: {}", self.step, self.error)
+    }
+}
+
+impl Error for GradientParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// how a gradient is laid over text
+///
+/// text is treated as a grid of lines and columns, line breaks are never
+/// colored, and the diagonal and radial layouts count each row as two
+/// columns since terminal cells are about twice as tall as they are wide
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum GradientLayout {
+    /// left to right, every line spans the width of the widest line
+    #[default]
+    Horizontal,
+    /// top to bottom, one color per line
+    Vertical,
+    /// along an angle in degrees, clockwise from left to right, so `90.0`
+    /// is top to bottom and `45.0` runs from the top left corner to the
+    /// bottom right one
+    Diagonal(f32),
+    /// outwards from a center given relative to the text block, `(0.5,
+    /// 0.5)` being the middle
+    Radial { x: f32, y: f32 },
+}
+
+/// remaps a gradient position before colors are looked up
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Easing {
+    /// no remapping
+    #[default]
+    Linear,
+    /// starts slow, same curve as css `ease-in`
+    EaseIn,
+    /// ends slow, same curve as css `ease-out`
+    EaseOut,
+    /// starts and ends slow, same curve as css `ease-in-out`
+    EaseInOut,
+    /// css style `cubic-bezier(x1, y1, x2, y2)`, x values are clamped to
+    /// `0.0..=1.0`
+    CubicBezier(f32, f32, f32, f32),
+    /// `n` flat bands, the first and last use the end colors
+    Steps(u32),
+}
+
+/// what happens to positions outside of the range covered by the stops
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Spread {
+    /// keep using the first / last stop's color
+    #[default]
+    Pad,
+    /// start over from the first stop, like css `repeating-*-gradient`
+    Repeat,
+    /// run back and forth between the first and last stop
+    Reflect,
+}
+
+impl Gradient {
+    /// creates an empty gradient, add colors with [`Gradient::stop`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// creates a gradient with the colors spread evenly from 0 to 1
+    pub fn even<I, C>(colors: I) -> Self
+    where
+        I: IntoIterator<Item = C>,
+        C: Into<Color>,
+    {
+        let colors: Vec<Color> = colors.into_iter().map(Into::into).collect();
+        let last = colors.len().saturating_sub(1).max(1) as f32;
+        Gradient {
+            stops: colors
+                .into_iter()
+                .enumerate()
+                .map(|(i, c)| (i as f32 / last, c))
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    /// parses colors and spreads them evenly, like `text_gradient` does
+    pub fn parse(steps: &[&str]) -> Result<Self, GradientParseError> {
+        let colors = steps
+            .iter()
+            .enumerate()
+            .map(|(step, s)| {
+                s.parse()
+                    .map_err(|error| GradientParseError { step, error })
+            })
+            .collect::<Result<Vec<Color>, _>>()?;
+        Ok(Self::even(colors))
+    }
+
+    /// adds a stop, `position` is clamped to `0.0..=1.0`
+    pub fn stop(mut self, position: f32, color: impl Into<Color>) -> Self {
+        let position = if position.is_nan() {
+            0.0
+        } else {
+            position.clamp(0.0, 1.0)
+        };
+        // insert after stops at the same position to keep hard edges
+        let index = self.stops.partition_point(|(p, _)| *p <= position);
+        self.stops.insert(index, (position, color.into()));
+        self
+    }
+
+    /// sets the color space colors are interpolated in
+    pub fn space(mut self, space: GradientSpace) -> Self {
+        self.space = space;
+        self
+    }
+
+    /// sets the easing function
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// sets what happens outside of the range covered by the stops
+    pub fn spread(mut self, spread: Spread) -> Self {
+        self.spread = spread;
+        self
+    }
+
+    /// sets how the gradient is laid over text
+    pub fn layout(mut self, layout: GradientLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// the stops as `(position, color)` pairs, sorted by position
+    pub fn stops(&self) -> &[(f32, Color)] {
+        &self.stops
+    }
+
+    /// color at position `t`, an empty gradient is black everywhere
+    pub fn sample(&self, t: f32) -> Color {
+        let (first, last) = match (self.stops.first(), self.stops.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Color::Rgb(0, 0, 0),
+        };
+
+        let t = self.spread_position(if t.is_nan() { 0.0 } else { t }, first.0, last.0);
+        let t = self.easing.apply(t);
+
+        if t <= first.0 {
+            return first.1;
+        }
+        if t >= last.0 {
+            return last.1;
+        }
+
+        let next = self.stops.partition_point(|(p, _)| *p <= t);
+        let (p0, c0) = self.stops[next - 1];
+        let (p1, c1) = self.stops[next];
+        if p1 <= p0 {
+            return c1;
+        }
+        self.space.mix(c0, c1, (t - p0) / (p1 - p0))
+    }
+
+    /// color of the cell at column `x` and line `y` of a `cols` by `rows`
+    /// text block, following the gradient's layout
+    pub fn sample_cell(&self, x: usize, y: usize, cols: usize, rows: usize) -> Color {
+        self.sample(self.layout.position(x, y, cols, rows))
+    }
+
+    /// `n` colors sampled evenly from start to end
+    pub fn colors(&self, n: usize) -> Vec<Color> {
+        let last = n.saturating_sub(1).max(1) as f32;
+        (0..n).map(|i| self.sample(i as f32 / last)).collect()
+    }
+
+    fn spread_position(&self, t: f32, start: f32, end: f32) -> f32 {
+        let len = end - start;
+        if len <= 0.0 {
+            return t.clamp(0.0, 1.0);
+        }
+
+        match self.spread {
+            Spread::Pad => t.clamp(0.0, 1.0),
+            _ if (start..=end).contains(&t) => t,
+            Spread::Repeat => start + (t - start).rem_euclid(len),
+            Spread::Reflect => {
+                let cycle = (t - start).rem_euclid(2.0 * len);
+                start
+                    + if cycle > len {
+                        2.0 * len - cycle
+                    } else {
+                        cycle
+                    }
+            }
+        }
+    }
+}
+
+impl GradientLayout {
+    /// gradient position of the cell at column `x` and line `y` in a block
+    /// of `cols` by `rows` cells
+    pub fn position(self, x: usize, y: usize, cols: usize, rows: usize) -> f32 {
+        let span = |n: usize| n.saturating_sub(1).max(1) as f32;
+        let (x, y) = (x as f32, y as f32);
+        // terminal cells are about twice as tall as wide
+        let (w, h) = (
+            cols.saturating_sub(1) as f32,
+            rows.saturating_sub(1) as f32 * 2.0,
+        );
+
+        match self {
+            GradientLayout::Horizontal => x / span(cols),
+            GradientLayout::Vertical => y / span(rows),
+            GradientLayout::Diagonal(angle) => {
+                let (sin, cos) = angle.to_radians().sin_cos();
+                let project = |x: f32, y: f32| x * cos + y * sin;
+                let corners = [
+                    project(0.0, 0.0),
+                    project(w, 0.0),
+                    project(0.0, h),
+                    project(w, h),
+                ];
+                let min = corners.iter().copied().fold(f32::INFINITY, f32::min);
+                let max = corners.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+                if max - min <= f32::EPSILON {
+                    return 0.0;
+                }
+                (project(x, y * 2.0) - min) / (max - min)
+            }
+            GradientLayout::Radial { x: cx, y: cy } => {
+                let (cx, cy) = (cx * w, cy * h);
+                let distance = |x: f32, y: f32| ((x - cx).powi(2) + (y - cy).powi(2)).sqrt();
+                let max = [
+                    distance(0.0, 0.0),
+                    distance(w, 0.0),
+                    distance(0.0, h),
+                    distance(w, h),
+                ]
+                .into_iter()
+                .fold(0.0, f32::max);
+                if max <= f32::EPSILON {
+                    return 0.0;
+                }
+                distance(x, y * 2.0) / max
+            }
+        }
+    }
+}
+
+impl Easing {
+    /// remaps `t`, which is expected to be in `0.0..=1.0`
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => cubic_bezier(0.42, 0.0, 1.0, 1.0, t),
+            Easing::EaseOut => cubic_bezier(0.0, 0.0, 0.58, 1.0, t),
+            Easing::EaseInOut => cubic_bezier(0.42, 0.0, 0.58, 1.0, t),
+            Easing::CubicBezier(x1, y1, x2, y2) => {
+                cubic_bezier(x1.clamp(0.0, 1.0), y1, x2.clamp(0.0, 1.0), y2, t)
+            }
+            Easing::Steps(0) | Easing::Steps(1) => 0.0,
+            Easing::Steps(n) => {
+                let band = (t * n as f32).floor().min(n as f32 - 1.0);
+                band / (n - 1) as f32
+            }
+        }
+    }
+}
+
+/// evaluates a css cubic bezier timing curve at `x`
+fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, x: f32) -> f32 {
+    if x <= 0.0 || x >= 1.0 {
+        return x;
+    }
+
+    let bezier = |a: f32, b: f32, s: f32| {
+        let inv = 1.0 - s;
+        3.0 * inv * inv * s * a + 3.0 * inv * s * s * b + s * s * s
+    };
+
+    // find the curve parameter for x by bisection, the curve is monotonic
+    // in x because both x control points are within 0..=1
+    let (mut low, mut high) = (0.0f32, 1.0f32);
+    let mut s = x;
+    for _ in 0..32 {
+        let found = bezier(x1, x2, s);
+        if (found - x).abs() < 1e-5 {
+            break;
+        }
+        if found < x {
+            low = s;
+        } else {
+            high = s;
+        }
+        s = (low + high) / 2.0;
+    }
+
+    bezier(y1, y2, s)
+}
+
+/// text with foreground and background gradients and attributes applied
+/// in a single pass, created by `Colors::gradient`
+///
+/// example:
+/// ```rs
+/// let fg = Gradient::parse(&["white", "gold"])?;
+/// let bg = Gradient::parse(&["#ff0080", "#0070f3"])?;
+/// println!("{}", "hi!".gradient().fg(fg).bg(bg).bold());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct GradientText {
+    text: String,
+    fg: Option<Gradient>,
+    bg: Option<Gradient>,
+    attributes: Vec<u8>,
+}
+
+impl GradientText {
+    pub(crate) fn new(text: String) -> Self {
+        GradientText {
+            text,
+            fg: None,
+            bg: None,
+            attributes: Vec::new(),
+        }
+    }
+
+    /// sets the text gradient
+    pub fn fg(mut self, gradient: Gradient) -> Self {
+        self.fg = Some(gradient);
+        self
+    }
+
+    /// sets the background gradient
+    pub fn bg(mut self, gradient: Gradient) -> Self {
+        self.bg = Some(gradient);
+        self
+    }
+
+    fn attribute(mut self, code: u8) -> Self {
+        if !self.attributes.contains(&code) {
+            self.attributes.push(code);
+        }
+        self
+    }
+
+    /// makes every character bold
+    pub fn bold(self) -> Self {
+        self.attribute(1)
+    }
+
+    /// makes every character dimmer
+    pub fn dim(self) -> Self {
+        self.attribute(2)
+    }
+
+    /// makes every character italic
+    pub fn italic(self) -> Self {
+        self.attribute(3)
+    }
+
+    /// underlines every character
+    pub fn underline(self) -> Self {
+        self.attribute(4)
+    }
+
+    /// makes every character blink
+    pub fn blink(self) -> Self {
+        self.attribute(5)
+    }
+
+    /// swaps foreground and background of every character
+    pub fn reverse(self) -> Self {
+        self.attribute(7)
+    }
+
+    /// strikes every character through
+    pub fn strikethrough(self) -> Self {
+        self.attribute(9)
+    }
+
+    /// renders the text
+    pub fn render(&self) -> String {
+        paint(
+            &self.text,
+            self.fg.as_ref(),
+            self.bg.as_ref(),
+            &self.attributes,
+        )
+    }
+}
+
+impl fmt::Display for GradientText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render())
+    }
+}
+
+/// colors every visible character of `text` along the gradients and sets
+/// `attributes` on it
+///
+/// the text is laid out as a grid of lines and terminal cells, so wide
+/// characters span two cells, escape sequences already in the text are
+/// kept and don't take up cells, line breaks are left
+/// uncolored and every line is reset before its break so backgrounds
+/// don't bleed, styles from the text are reopened on the next line
+pub(crate) fn paint(
+    text: &str,
+    fg: Option<&Gradient>,
+    bg: Option<&Gradient>,
+    attributes: &[u8],
+) -> String {
+    let fg = fg.filter(|g| !g.stops().is_empty());
+    let bg = bg.filter(|g| !g.stops().is_empty());
+    let nothing_to_do = fg.is_none() && bg.is_none() && attributes.is_empty();
+    if text.is_empty() || nothing_to_do || !term::colors_enabled() {
+        return text.to_string();
+    }
+
+    // counts the columns characters start in, so a wide last character
+    // still gets the end of the gradient
+    let (mut cols, mut rows, mut x) = (0, 1, 0);
+    for segment in ansi::segments(text) {
+        if let Segment::Text(t) = segment {
+            for g in graphemes(t) {
+                match g {
+                    "\n" | "\r\n" => {
+                        rows += 1;
+                        x = 0;
+                    }
+                    "\r" => {}
+                    _ => {
+                        cols = cols.max(x + 1);
+                        x += grapheme_width(g);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut result = String::new();
+    // sgr sequences from the text that are still in effect
+    let mut active = String::new();
+    let mut colored = false;
+    let (mut x, mut y) = (0, 0);
+
+    for segment in ansi::segments(text) {
+        let t = match segment {
+            Segment::Escape(e) => {
+                if ansi::is_reset(e) {
+                    active.clear();
+                    colored = false;
+                } else if ansi::is_sgr(e) {
+                    active.push_str(e);
+                }
+                result.push_str(e);
+                continue;
+            }
+            Segment::Text(t) => t,
+        };
+
+        for g in graphemes(t) {
+            match g {
+                "\n" | "\r\n" => {
+                    if colored {
+                        result.push_str("\x1B[0m");
+                        colored = false;
+                    }
+                    result.push_str(g);
+                    result.push_str(&active);
+                    x = 0;
+                    y += 1;
+                }
+                "\r" => result.push('\r'),
+                _ => {
+                    let mut params: Vec<String> = attributes.iter().map(u8::to_string).collect();
+                    // always go through rgb so stops that are basic colors
+                    // don't come out in the terminal's own palette
+                    if let Some(g) = fg {
+                        let rgb = g.sample_cell(x, y, cols, rows).to_rgb();
+                        params.push(Color::from(rgb).fg_params());
+                    }
+                    if let Some(g) = bg {
+                        let rgb = g.sample_cell(x, y, cols, rows).to_rgb();
+                        params.push(Color::from(rgb).bg_params());
+                    }
+                    result.push_str(&format!("\x1B[{}m{}", params.join(";"), g));
+                    colored = true;
+                    // wide characters take up two cells of the gradient
+                    x += grapheme_width(g);
+                }
+            }
+        }
+    }
+
+    if colored {
+        result.push_str("\x1B[0m");
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::term::colored;
+
+    #[test]
+    fn parse_errors_name_the_step() {
+        let error = Gradient::parse(&["red", "#zz0000"]).unwrap_err();
+        assert_eq!(error.step(), 1);
+        assert_eq!(
+            *error.error(),
+            ColorParseError::InvalidHexDigit {
+                offset: 1,
+                found: 'z'
+            }
+        );
+        assert_eq!(error.to_string(), "step 1: invalid hex digit 'z' at byte 1");
+    }
+
+    #[test]
+    fn wide_characters_take_two_cells() {
+        let g = Gradient::even([Color::Rgb(255, 0, 0), Color::Rgb(0, 0, 255)]);
+        let s = colored(|| paint("日ab", Some(&g), None, &[]));
+        // 日 covers cells 0 and 1, so `a` is in cell 2 of 4
+        let a = colored(|| g.sample(2.0 / 3.0).fg_params());
+        assert!(s.contains(&format!("\x1B[{}ma", a)), "{:?}", s);
+    }
+
+    #[test]
+    fn wide_last_characters_reach_the_end() {
+        let g = Gradient::even([Color::Rgb(255, 0, 0), Color::Rgb(0, 0, 255)]);
+        let s = colored(|| paint("ab日", Some(&g), None, &[]));
+        let (a, b, wide) = colored(|| {
+            let params = |t: f32| g.sample(t).fg_params();
+            (params(0.0), params(0.5), params(1.0))
+        });
+        let expected = format!("\x1B[{}ma\x1B[{}mb\x1B[{}m日\x1B[0m", a, b, wide);
+        assert_eq!(s, expected);
+    }
+
+    #[test]
+    fn combining_marks_stay_with_their_base() {
+        let g = Gradient::even([Color::Rgb(255, 0, 0), Color::Rgb(0, 0, 255)]);
+        let s = colored(|| paint("e\u{301}b", Some(&g), None, &[]));
+        let b = colored(|| g.sample(1.0).fg_params());
+        assert!(s.contains("e\u{301}\x1B["), "{:?}", s);
+        assert!(s.contains(&format!("\x1B[{}mb", b)), "{:?}", s);
+    }
+}
@@ -1,80 +1,70 @@
 use std::fmt::Display;
 
-#[derive(Debug, Clone, Copy)]
-struct RGB {
-    r: u8,
-    g: u8,
-    b: u8,
-}
-
-#[allow(dead_code)]
-impl RGB {
-    fn new(r: u8, g: u8, b: u8) -> Self {
-        RGB { r, g, b }
-    }
-
-    fn to_hex(&self) -> String {
-        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
-    }
-}
-
-fn hex_to_rgb(hex: &str) -> Option<RGB> {
-    let hex = hex.trim_start_matches('#');
-    if hex.len() != 6 {
-        return None;
-    }
+mod animation;
+mod ansi;
+mod color;
+mod css;
+mod fade;
+mod gradient;
+mod panel;
+mod parse;
+mod progress;
+mod space;
+mod spinner;
+mod style;
+mod table;
+mod term;
+mod truncate;
+mod width;
+mod wrap;
+
+pub use animation::{Animation, Effect};
+pub use ansi::{strip_ansi, tokens, visible_len, Sgr, Token, Tokens};
+pub use color::{Color, NamedColor};
+pub use fade::{FadeDirection, FadeOptions};
+pub use gradient::{
+    Easing, Gradient, GradientLayout, GradientParseError, GradientSpace, GradientText, HueArc,
+    Spread,
+};
+pub use panel::{Align, Border, Panel, Sides};
+pub use parse::{ColorParseError, ColorParser};
+pub use progress::{BarColor, BarFill, MultiProgress, ProgressBar};
+pub use spinner::{Frames, Spinner, SpinnerHandle};
+pub use style::{Attributes, Style, StyleParseError, Styled, Transition};
+pub use table::{Column, Overflow, Table};
+pub use term::{
+    color_choice, color_level, colors_enabled, set_color_choice, set_color_level, set_color_stream,
+    with_color_choice, with_color_level, ColorChoice, ColorLevel, Stream,
+};
+pub use truncate::{TruncateAt, TruncateOptions};
+pub use width::display_width;
+pub use wrap::{WrapMode, WrapOptions};
 
-    let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
-    let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
-    let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
-
-    Some(RGB::new(r, g, b))
-}
-
-fn is_hex_color(s: &str) -> bool {
-    let s = s.trim_start_matches('#');
-    s.len() == 6 && s.chars().all(|c| c.is_ascii_hexdigit())
-}
-
-fn is_rgb_color(s: &str) -> bool {
-    s.starts_with("rgb(") && s.ends_with(')')
-}
-
-fn parse_rgb(s: &str) -> Option<RGB> {
-    let contents = s.trim_start_matches("rgb(").trim_end_matches(')');
-    let parts: Vec<&str> = contents.split(',').collect();
-    if parts.len() != 3 {
-        return None;
-    }
-
-    let r = parts[0].trim().parse().ok()?;
-    let g = parts[1].trim().parse().ok()?;
-    let b = parts[2].trim().parse().ok()?;
-
-    Some(RGB::new(r, g, b))
-}
+/// implements methods for beautifying your code
+pub trait Colors {
+    /// starts a [`Styled`] chain, which only writes escapes when displayed
+    ///
+    /// example:
+    /// ```rs
+    /// println!("{}", "hi!".styled().bold().text_red().bg_black());
+    /// ```
+    fn styled(&self) -> Styled<'_, Self>;
 
-fn identify_color_type(s: &str) -> ColorType {
-    if is_hex_color(s) {
-        ColorType::Hex
-    } else if is_rgb_color(s) {
-        ColorType::RGB
-    } else if s.starts_with("text_") || s.starts_with("bg_") {
-        ColorType::Accent
-    } else {
-        ColorType::Default
-    }
-}
+    /// applies a whole [`Style`] at once
+    ///
+    /// example:
+    /// ```rs
+    /// let error: Style = "bold red on black".parse()?;
+    /// println!("{}", "failed".style(error));
+    /// ```
+    fn style(&self, style: Style) -> String;
 
-enum ColorType {
-    Hex,
-    RGB,
-    Accent,
-    Default,
-}
+    // arbitrary colors
+    /// sets text color to any [`Color`], e.g. one computed at runtime
+    fn text_color(&self, color: Color) -> String;
+    /// sets background color to any [`Color`], e.g. one computed at runtime
+    fn bg_color(&self, color: Color) -> String;
 
-/// implements methods for beautifying your code
-pub trait Colors {
     // default colors
     /// sets text color to default terminal color
     fn text_default(&self) -> String;
@@ -556,18 +546,22 @@ pub trait Colors {
 
     // gradient colors
     /// give the text gradient color by describing gradient steps
-    /// argument can be hexagonal, rgb, default or an accent color
+    /// argument can be hexagonal, rgb, default or an accent color,
+    /// anything [`Color`] can parse is accepted
     ///
-    /// it's better to use with longer texts for better results
+    /// it's better to use with longer texts for better results, multi-line
+    /// text gets the same gradient on every line and escape sequences
+    /// already in the text are kept as they are
     ///
     /// example:
     /// ```rs
     /// println!("{}", "hi!".text_gradient(["#f40420", "text_blue_500"]))
     /// ```
-    fn text_gradient(&self, steps: &[&'static str]) -> String;
+    fn text_gradient(&self, steps: &[&str]) -> String;
 
     /// give the background gradient color by describing gradient steps
-    /// argument can be hexagonal, rgb, default or an accent color
+    /// argument can be hexagonal, rgb, default or an accent color,
+    /// anything [`Color`] can parse is accepted
     ///
     /// it's better to use with longer texts for better results
     ///
@@ -575,7 +569,54 @@ pub trait Colors {
     /// ```rs
     /// println!("{}", "hi!".bg_gradient(["#f40420", "text_blue_500"]))
     /// ```
-    fn bg_gradient(&self, steps: &[&'static str]) -> String;
+    fn bg_gradient(&self, steps: &[&str]) -> String;
+
+    /// same as `text_gradient`, but interpolates in the given color space
+    ///
+    /// example:
+    /// ```rs
+    /// println!("{}", "hi!".text_gradient_in(&["red", "lime"], GradientSpace::Oklab))
+    /// ```
+    fn text_gradient_in(&self, steps: &[&str], space: GradientSpace) -> String;
+
+    /// same as `bg_gradient`, but interpolates in the given color space
+    fn bg_gradient_in(&self, steps: &[&str], space: GradientSpace) -> String;
+
+    /// colors the text along a prebuilt [`Gradient`]
+    ///
+    /// example:
+    /// ```rs
+    /// let g = Gradient::new().stop(0.0, Color::from(0xff7e5f)).stop(0.8, "gold".parse()?);
+    /// println!("{}", "hi!".text_gradient_with(&g))
+    /// ```
+    fn text_gradient_with(&self, gradient: &Gradient) -> String;
+
+    /// colors the background along a prebuilt [`Gradient`]
+    fn bg_gradient_with(&self, gradient: &Gradient) -> String;
+
+    /// starts a gradient that can combine text and background gradients
+    /// and attributes in a single pass
+    ///
+    /// example:
+    /// ```rs
+    /// let fg = Gradient::parse(&["white", "gold"])?;
+    /// let bg = Gradient::parse(&["#ff0080", "#0070f3"])?;
+    /// println!("{}", "hi!".gradient().fg(fg).bg(bg).bold())
+    /// ```
+    fn gradient(&self) -> GradientText;
+
+    /// same as `text_gradient`, but fails on the first step that isn't a
+    /// valid color instead of skipping it
+    ///
+    /// example:
+    /// ```rs
+    /// let s = "hi!".try_text_gradient(&["#f40420", "#3b82f6"])?;
+    /// ```
+    fn try_text_gradient(&self, steps: &[&str]) -> Result<String, GradientParseError>;
+
+    /// same as `bg_gradient`, but fails on the first step that isn't a
+    /// valid color instead of skipping it
+    fn try_bg_gradient(&self, steps: &[&str]) -> Result<String, GradientParseError>;
 
     /// Makes text bold
     fn bold(&self) -> String;
@@ -593,6 +634,9 @@ pub trait Colors {
     fn dim(&self) -> String;
 
     /// Makes text blink
+    ///
+    /// Many terminals ignore blinking, [`Spinner`] draws an animation that
+    /// shows everywhere
     fn blink(&self) -> String;
 
     /// Swaps foreground and background colors
@@ -604,13 +648,29 @@ pub trait Colors {
     /// Makes text blink slowly
     fn blink_slow(&self) -> String;
 
-    /// Creates a fade-in effect using different brightness levels
+    /// Fades text in from the background, its own colors blended along
+    /// its length
     fn fade_in(&self) -> String;
 
-    /// Creates a fade-out effect using different brightness levels
+    /// Fades text out into the background, its own colors blended along
+    /// its length
     fn fade_out(&self) -> String;
 
+    /// Blends the text's colors towards the background along its length
+    ///
+    /// takes a [`FadeDirection`] or [`FadeOptions`], see [`Animation`] for
+    /// fades that play over time
+    ///
+    /// example:
+    /// ```rs
+    /// println!("{}", "more below".text_blue().fade(FadeDirection::Out));
+    /// ```
+    fn fade(&self, options: impl Into<FadeOptions>) -> String;
+
     /// Pads text with spaces on the left to reach given width
+    ///
+    /// widths are terminal columns as measured by [`display_width`], so
+    /// styled text, wide characters and emoji line up
     fn pad_left(&self, width: usize) -> String;
     
     /// Pads text with spaces on the right to reach given width
@@ -619,15 +679,50 @@ pub trait Colors {
     /// Centers text within specified width
     fn center(&self, width: usize) -> String;
 
-    /// Surrounds text with a box
+    /// Breaks text into lines that fit a width, keeping styles intact
+    ///
+    /// takes a width or [`WrapOptions`]
+    ///
+    /// example:
+    /// ```rs
+    /// for line in paragraph.text_gradient(&["red", "blue"]).wrap(40) {
+    ///     println!("{}", line);
+    /// }
+    /// ```
+    fn wrap(&self, options: impl Into<WrapOptions>) -> Vec<String>;
+
+    /// Same as `wrap`, with the lines joined by line breaks
+    fn fill(&self, options: impl Into<WrapOptions>) -> String;
+
+    /// Cuts text to a width, marking the cut with an ellipsis
+    ///
+    /// takes a width or [`TruncateOptions`], escapes and characters are
+    /// never split and styles open at the cut are closed
+    ///
+    /// example:
+    /// ```rs
+    /// println!("{}", path.text_blue().truncate_width(TruncateOptions::new(24).at(TruncateAt::Middle)));
+    /// ```
+    fn truncate_width(&self, options: impl Into<TruncateOptions>) -> String;
+
+    /// Surrounds text with a box, one row per line
     fn box_it(&self) -> String;
 
-    /// Adds a single-line border around text
+    /// Adds a single-line border with rounded corners around text
     fn border(&self) -> String;
 
     /// Adds a double-line border around text
     fn double_border(&self) -> String;
 
+    /// Starts a [`Panel`], a box with a title, padding, alignment and a
+    /// colored border
+    ///
+    /// example:
+    /// ```rs
+    /// println!("{}", "all good\nno warnings".panel().title("build").padding(1, 0));
+    /// ```
+    fn panel(&self) -> Panel;
+
     // helpers
     fn code(&self, code: usize) -> String;
     fn codes(&self, initial: usize, codes: (usize, usize, usize)) -> String;
@@ -637,92 +732,65 @@ impl<T> Colors for T
 where
     T: Display,
 {
+    fn styled(&self) -> Styled<'_, Self> {
+        Styled::new(self)
+    }
+
+    fn style(&self, style: Style) -> String {
+        style.paint(self).to_string()
+    }
+
     // helpers
     fn code(&self, code: usize) -> String {
-        let s = self.to_string();
-
-        if s.contains("\x1B[") {
-            // Split by reset code but keep the separators
-            let parts: Vec<&str> = s.split_inclusive("\x1B[0m").collect();
-            let mut result = String::new();
-
-            for part in parts {
-                if part.starts_with("\x1B[") {
-                    // This part already has color formatting, keep it as is
-                    result.push_str(part);
-                } else {
-                    // This part has no color formatting, apply new color
-                    result.push_str(&format!("\x1B[{}m{}", code, part));
-                }
-            }
-            result
-        } else {
-            // No existing formatting, apply color normally
-            format!("\x1B[{}m{}\x1B[0m", code, s)
+        match Style::from_code(code) {
+            Some(style) => style.paint(self).to_string(),
+            None => paint(self.to_string(), &code.to_string()),
         }
     }
 
     fn codes(&self, initial: usize, (a, b, c): (usize, usize, usize)) -> String {
-        let s = self.to_string();
-
-        if s.contains("\x1B[") {
-            let parts: Vec<&str> = s.split_inclusive("\x1B[0m").collect();
-            let mut result = String::new();
-
-            for part in parts {
-                if part.starts_with("\x1B[") {
-                    // Keep existing color formatting
-                    result.push_str(part);
-                } else {
-                    // Apply new color to unformatted text
-                    result.push_str(&format!("\x1B[{};2;{};{};{}m{}", initial, a, b, c, part));
-                }
-            }
-            result
-        } else {
-            format!("\x1B[{};2;{};{};{}m{}\x1B[0m", initial, a, b, c, s)
+        let color = Color::Rgb(a.min(255) as u8, b.min(255) as u8, c.min(255) as u8);
+        match initial {
+            38 => self.text_color(color),
+            48 => self.bg_color(color),
+            _ => paint(
+                self.to_string(),
+                &format!("{};2;{};{};{}", initial, a, b, c),
+            ),
         }
     }
 
+    fn text_color(&self, color: Color) -> String {
+        self.styled().text_color(color).to_string()
+    }
+
+    fn bg_color(&self, color: Color) -> String {
+        self.styled().bg_color(color).to_string()
+    }
+
     fn blink_fast(&self) -> String {
-          // Some terminals support code 6 for rapid blink
-          format!("\x1B[6m{}\x1B[0m", self)
-      }
-  
-      fn blink_slow(&self) -> String {
-          // Using standard blink (code 5)
-          format!("\x1B[5m{}\x1B[0m", self)
-      }
-  
-      fn fade_in(&self) -> String {
-          let s = self.to_string();
-          let mut result = String::new();
-          let brightnesses = [232, 236, 240, 244, 248, 252]; // Using greyscale colors
-          
-          for (i, c) in s.chars().enumerate() {
-              let brightness = brightnesses[i % brightnesses.len()];
-              result.push_str(&format!("\x1B[38;5;{}m{}", brightness, c));
-          }
-          result.push_str("\x1B[0m");
-          result
-      }
-  
-      fn fade_out(&self) -> String {
-          let s = self.to_string();
-          let mut result = String::new();
-          let brightnesses = [252, 248, 244, 240, 236, 232]; // Reverse greyscale
-          
-          for (i, c) in s.chars().enumerate() {
-              let brightness = brightnesses[i % brightnesses.len()];
-              result.push_str(&format!("\x1B[38;5;{}m{}", brightness, c));
-          }
-          result.push_str("\x1B[0m");
-          result
-      }
+        self.styled().blink_fast().to_string()
+    }
+
+    fn blink_slow(&self) -> String {
+        self.styled().blink_slow().to_string()
+    }
+
+    fn fade_in(&self) -> String {
+        self.fade(FadeDirection::In)
+    }
+
+    fn fade_out(&self) -> String {
+        self.fade(FadeDirection::Out)
+    }
+
+    fn fade(&self, options: impl Into<FadeOptions>) -> String {
+        fade::fade(&self.to_string(), &options.into())
+    }
 
     fn pad_left(&self, width: usize) -> String {
          let s = self.to_string();
-         let len = s.chars().count();
+         let len = display_width(&s);
          if len >= width {
              return s;
          }
@@ -731,7 +799,7 @@ where
  
      fn pad_right(&self, width: usize) -> String {
          let s = self.to_string();
-         let len = s.chars().count();
+         let len = display_width(&s);
          if len >= width {
              return s;
          }
@@ -740,7 +808,7 @@ where
 
     fn center(&self, width: usize) -> String {
         let s = self.to_string();
-        let len = s.chars().count();
+        let len = display_width(&s);
         if len >= width {
             return s;
         }
@@ -749,22 +817,32 @@ where
         format!("{}{}{}", " ".repeat(left_pad), s, " ".repeat(right_pad))
     }
 
+    fn wrap(&self, options: impl Into<WrapOptions>) -> Vec<String> {
+        wrap::wrap(&self.to_string(), &options.into())
+    }
+
+    fn fill(&self, options: impl Into<WrapOptions>) -> String {
+        self.wrap(options).join("\n")
+    }
+
+    fn truncate_width(&self, options: impl Into<TruncateOptions>) -> String {
+        truncate::truncate(&self.to_string(), &options.into())
+    }
+
     fn box_it(&self) -> String {
-        let s = self.to_string();
-        let width = s.chars().count();
-        format!("┌{}┐\n│{}│\n└{}┘", "─".repeat(width), s, "─".repeat(width))
+        self.panel().to_string()
     }
 
     fn border(&self) -> String {
-        let s = self.to_string();
-        let width = s.chars().count();
-        format!("╭{}╮\n│{}│\n╰{}╯", "─".repeat(width), s, "─".repeat(width))
+        self.panel().border(Border::Rounded).to_string()
     }
 
     fn double_border(&self) -> String {
-        let s = self.to_string();
-        let width = s.chars().count();
-        format!("╔{}╗\n║{}║\n╚{}╝", "═".repeat(width), s, "═".repeat(width))
+        self.panel().border(Border::Double).to_string()
+    }
+
+    fn panel(&self) -> Panel {
+        Panel::new(self)
     }
 
     fn bold(&self) -> String {
@@ -1387,121 +1465,77 @@ where
     }
 
     // gradient
-    fn text_gradient(&self, steps: &[&'static str]) -> String {
-        if steps.is_empty() {
-            return self.to_string();
-        }
-
-        let text = self.to_string();
-        if text.is_empty() {
-            return text;
-        }
+    fn text_gradient(&self, steps: &[&str]) -> String {
+        self.text_gradient_in(steps, GradientSpace::Srgb)
+    }
 
-        let mut result = String::new();
-        let char_count = text.chars().count();
-
-        let mut colors: Vec<RGB> = Vec::new();
-        for step in steps {
-            match identify_color_type(step) {
-                ColorType::Hex => {
-                    if let Some(rgb) = hex_to_rgb(step) {
-                        colors.push(rgb);
-                    }
-                }
-                ColorType::RGB => {
-                    if let Some(rgb) = parse_rgb(step) {
-                        colors.push(rgb);
-                    }
-                }
-                _ => {}
-            }
-        }
+    fn bg_gradient(&self, steps: &[&str]) -> String {
+        self.bg_gradient_in(steps, GradientSpace::Srgb)
+    }
 
+    fn text_gradient_in(&self, steps: &[&str], space: GradientSpace) -> String {
+        let colors: Vec<Color> = steps.iter().filter_map(|step| step.parse().ok()).collect();
         if colors.len() < 2 {
-            return text;
-        }
-
-        for (i, c) in text.chars().enumerate() {
-            let position = i as f32 / (char_count - 1) as f32;
-
-            let segment = position * (colors.len() - 1) as f32;
-            let index = segment.floor() as usize;
-            let next_index = (index + 1).min(colors.len() - 1);
-
-            let blend = segment.fract();
-
-            let current_color = &colors[index];
-            let next_color = &colors[next_index];
-
-            let r = lerp(current_color.r as f32, next_color.r as f32, blend) as u8;
-            let g = lerp(current_color.g as f32, next_color.g as f32, blend) as u8;
-            let b = lerp(current_color.b as f32, next_color.b as f32, blend) as u8;
-
-            result.push_str(&format!("\x1B[38;2;{};{};{}m{}", r, g, b, c));
+            return self.to_string();
         }
-
-        result.push_str("\x1B[0m");
-        result
+        gradient::paint(
+            &self.to_string(),
+            Some(&Gradient::even(colors).space(space)),
+            None,
+            &[],
+        )
     }
 
-    fn bg_gradient(&self, steps: &[&'static str]) -> String {
-        if steps.is_empty() {
+    fn bg_gradient_in(&self, steps: &[&str], space: GradientSpace) -> String {
+        let colors: Vec<Color> = steps.iter().filter_map(|step| step.parse().ok()).collect();
+        if colors.len() < 2 {
             return self.to_string();
         }
-
-        let text = self.to_string();
-        if text.is_empty() {
-            return text;
-        }
-
-        let mut result = String::new();
-        let char_count = text.chars().count();
-
-        let mut colors: Vec<RGB> = Vec::new();
-        for step in steps {
-            match identify_color_type(step) {
-                ColorType::Hex => {
-                    if let Some(rgb) = hex_to_rgb(step) {
-                        colors.push(rgb);
-                    }
-                }
-                ColorType::RGB => {
-                    if let Some(rgb) = parse_rgb(step) {
-                        colors.push(rgb);
-                    }
-                }
-                _ => {}
-            }
+        gradient::paint(
+            &self.to_string(),
+            None,
+            Some(&Gradient::even(colors).space(space)),
+            &[],
+        )
+    }
+
+    fn try_text_gradient(&self, steps: &[&str]) -> Result<String, GradientParseError> {
+        let g = Gradient::parse(steps)?;
+        if g.stops().len() < 2 {
+            return Ok(self.to_string());
         }
+        Ok(gradient::paint(&self.to_string(), Some(&g), None, &[]))
+    }
 
-        if colors.len() < 2 {
-            return text;
+    fn try_bg_gradient(&self, steps: &[&str]) -> Result<String, GradientParseError> {
+        let g = Gradient::parse(steps)?;
+        if g.stops().len() < 2 {
+            return Ok(self.to_string());
         }
+        Ok(gradient::paint(&self.to_string(), None, Some(&g), &[]))
+    }
 
-        for (i, c) in text.chars().enumerate() {
-            let position = i as f32 / (char_count - 1) as f32;
-
-            let segment = position * (colors.len() - 1) as f32;
-            let index = segment.floor() as usize;
-            let next_index = (index + 1).min(colors.len() - 1);
-
-            let blend = segment.fract();
-
-            let current_color = &colors[index];
-            let next_color = &colors[next_index];
-
-            let r = lerp(current_color.r as f32, next_color.r as f32, blend) as u8;
-            let g = lerp(current_color.g as f32, next_color.g as f32, blend) as u8;
-            let b = lerp(current_color.b as f32, next_color.b as f32, blend) as u8;
+    fn gradient(&self) -> GradientText {
+        GradientText::new(self.to_string())
+    }
 
-            result.push_str(&format!("\x1B[48;2;{};{};{}m{}", r, g, b, c));
-        }
+    fn text_gradient_with(&self, gradient: &Gradient) -> String {
+        gradient::paint(&self.to_string(), Some(gradient), None, &[])
+    }
 
-        result.push_str("\x1B[0m");
-        result
+    fn bg_gradient_with(&self, gradient: &Gradient) -> String {
+        gradient::paint(&self.to_string(), None, Some(gradient), &[])
     }
 }
 
-fn lerp(start: f32, end: f32, t: f32) -> f32 {
-    start + (end - start) * t
+/// wraps `s` in the sgr sequence built from `params`, switching it back on
+/// after every reset inside `s`
+fn paint(s: String, params: &str) -> String {
+    if !colors_enabled() {
+        return s;
+    }
+
+    let mut result = String::with_capacity(s.len() + params.len() + 8);
+    let _ = style::write_nested(&mut result, &s, style::Outer::Params(params));
+    result
 }
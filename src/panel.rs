@@ -0,0 +1,471 @@
+use std::fmt::{self, Display};
+use std::ops::{BitOr, Sub};
+
+use crate::ansi;
+use crate::style::Style;
+use crate::width::display_width;
+
+/// where text sits within a wider space
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+impl Align {
+    /// pads `s` with spaces to `width` columns
+    pub(crate) fn pad(self, s: &str, width: usize) -> String {
+        let gap = width.saturating_sub(display_width(s));
+        let left = match self {
+            Align::Left => 0,
+            Align::Center => gap / 2,
+            Align::Right => gap,
+        };
+        format!("{}{}{}", " ".repeat(left), s, " ".repeat(gap - left))
+    }
+}
+
+/// the characters a [`Panel`] or a [`Table`] is drawn with
+///
+/// [`Table`]: crate::Table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Border {
+    /// single lines, `┌─┐│└┘`
+    #[default]
+    Square,
+    /// single lines with rounded corners, `╭─╮│╰╯`
+    Rounded,
+    /// double lines, `╔═╗║╚╝`
+    Double,
+    /// thick lines, `┏━┓┃┗┛`
+    Heavy,
+    /// dashed lines, `┌╌┐╎└┘`
+    Dashed,
+    /// plain ascii for terminals without box drawing characters, `+-+|++`
+    Ascii,
+    /// solid half blocks, `▛▀▜▌▐▙▄▟`
+    Block,
+    /// no border at all
+    None,
+}
+
+/// the characters of one [`Border`]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BorderChars {
+    pub(crate) top_left: char,
+    pub(crate) top: char,
+    pub(crate) top_right: char,
+    pub(crate) left: char,
+    pub(crate) right: char,
+    pub(crate) bottom_left: char,
+    pub(crate) bottom: char,
+    pub(crate) bottom_right: char,
+    // lines and junctions inside a table
+    pub(crate) horizontal: char,
+    pub(crate) vertical: char,
+    pub(crate) top_tee: char,
+    pub(crate) bottom_tee: char,
+    pub(crate) left_tee: char,
+    pub(crate) right_tee: char,
+    pub(crate) cross: char,
+}
+
+impl Border {
+    pub(crate) fn chars(self) -> BorderChars {
+        // corners clockwise from the top left, the horizontal and vertical
+        // lines, then the tees pointing down, up, right and left and the
+        // cross
+        let lines = |[tl, tr, br, bl, h, v, td, tu, tr_, tl_, x]: [char; 11]| BorderChars {
+            top_left: tl,
+            top: h,
+            top_right: tr,
+            left: v,
+            right: v,
+            bottom_left: bl,
+            bottom: h,
+            bottom_right: br,
+            horizontal: h,
+            vertical: v,
+            top_tee: td,
+            bottom_tee: tu,
+            left_tee: tr_,
+            right_tee: tl_,
+            cross: x,
+        };
+        match self {
+            Border::Square => lines(['┌', '┐', '┘', '└', '─', '│', '┬', '┴', '├', '┤', '┼']),
+            Border::Rounded => lines(['╭', '╮', '╯', '╰', '─', '│', '┬', '┴', '├', '┤', '┼']),
+            Border::Double => lines(['╔', '╗', '╝', '╚', '═', '║', '╦', '╩', '╠', '╣', '╬']),
+            Border::Heavy => lines(['┏', '┓', '┛', '┗', '━', '┃', '┳', '┻', '┣', '┫', '╋']),
+            Border::Dashed => lines(['┌', '┐', '┘', '└', '╌', '╎', '┬', '┴', '├', '┤', '┼']),
+            Border::Ascii => lines(['+', '+', '+', '+', '-', '|', '+', '+', '+', '+', '+']),
+            Border::Block => BorderChars {
+                top_left: '▛',
+                top: '▀',
+                top_right: '▜',
+                left: '▌',
+                right: '▐',
+                bottom_left: '▙',
+                bottom: '▄',
+                bottom_right: '▟',
+                horizontal: '▀',
+                vertical: '▐',
+                top_tee: '▜',
+                bottom_tee: '▟',
+                left_tee: '▛',
+                right_tee: '▜',
+                cross: '▜',
+            },
+            Border::None => lines([' '; 11]),
+        }
+    }
+}
+
+/// which edges of a [`Panel`] are drawn
+///
+/// sides combine with `|` and are removed with `-`
+///
+/// example:
+/// ```rs
+/// let rules = Sides::TOP | Sides::BOTTOM;
+/// let open_right = Sides::ALL - Sides::RIGHT;
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Sides(u8);
+
+impl Sides {
+    pub const NONE: Self = Sides(0);
+    pub const TOP: Self = Sides(1);
+    pub const RIGHT: Self = Sides(1 << 1);
+    pub const BOTTOM: Self = Sides(1 << 2);
+    pub const LEFT: Self = Sides(1 << 3);
+    pub const ALL: Self = Sides(0b1111);
+
+    /// whether every side in `other` is set
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl Default for Sides {
+    fn default() -> Self {
+        Sides::ALL
+    }
+}
+
+impl fmt::Debug for Sides {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = [
+            (Sides::TOP, "top"),
+            (Sides::RIGHT, "right"),
+            (Sides::BOTTOM, "bottom"),
+            (Sides::LEFT, "left"),
+        ];
+        let set: Vec<&str> = names
+            .iter()
+            .filter(|(side, _)| self.contains(*side))
+            .map(|(_, name)| *name)
+            .collect();
+        write!(f, "Sides({})", set.join(" | "))
+    }
+}
+
+impl BitOr for Sides {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Sides(self.0 | other.0)
+    }
+}
+
+impl Sub for Sides {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Sides(self.0 & !other.0)
+    }
+}
+
+/// text drawn inside a border, one row per line
+///
+/// the panel is as wide as the widest line, measured in terminal columns,
+/// so styled text, wide characters and emoji keep the edges straight
+///
+/// example:
+/// ```rs
+/// let panel = "3 passed\n1 failed".panel()
+///     .title("tests")
+///     .footer("0.42s")
+///     .footer_align(Align::Right)
+///     .border(Border::Heavy)
+///     .padding(1, 0)
+///     .margin(2, 1)
+///     .width(30)
+///     .border_style(Style::new().fg(Color::Ansi16(8)));
+/// println!("{}", panel);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Panel {
+    content: String,
+    title: Option<String>,
+    title_align: Align,
+    footer: Option<String>,
+    footer_align: Align,
+    border: Border,
+    border_style: Style,
+    sides: Sides,
+    padding: (usize, usize),
+    margin: (usize, usize),
+    width: Option<usize>,
+    align: Align,
+}
+
+impl Panel {
+    /// a square bordered panel around `content`
+    pub fn new(content: impl Display) -> Self {
+        Panel {
+            content: content.to_string(),
+            title: None,
+            title_align: Align::Left,
+            footer: None,
+            footer_align: Align::Left,
+            border: Border::default(),
+            border_style: Style::new(),
+            sides: Sides::ALL,
+            padding: (0, 0),
+            margin: (0, 0),
+            width: None,
+            align: Align::default(),
+        }
+    }
+
+    /// shows `title` in the top edge
+    pub fn title(mut self, title: impl Display) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// where the title sits in the top edge, left by default
+    pub fn title_align(mut self, align: Align) -> Self {
+        self.title_align = align;
+        self
+    }
+
+    /// shows `footer` in the bottom edge
+    pub fn footer(mut self, footer: impl Display) -> Self {
+        self.footer = Some(footer.to_string());
+        self
+    }
+
+    /// where the footer sits in the bottom edge, left by default
+    pub fn footer_align(mut self, align: Align) -> Self {
+        self.footer_align = align;
+        self
+    }
+
+    /// sets the border characters
+    pub fn border(mut self, border: Border) -> Self {
+        self.border = border;
+        self
+    }
+
+    /// colors and attributes of the border
+    pub fn border_style(mut self, style: Style) -> Self {
+        self.border_style = style;
+        self
+    }
+
+    /// which edges are drawn, all of them by default
+    pub fn sides(mut self, sides: Sides) -> Self {
+        self.sides = sides;
+        self
+    }
+
+    /// empty columns left and right of the text and empty rows above and
+    /// below it, inside the border
+    pub fn padding(mut self, horizontal: usize, vertical: usize) -> Self {
+        self.padding = (horizontal, vertical);
+        self
+    }
+
+    /// empty columns left and right of the panel and empty rows above and
+    /// below it, outside the border, so the panel fills a block of spaces
+    pub fn margin(mut self, horizontal: usize, vertical: usize) -> Self {
+        self.margin = (horizontal, vertical);
+        self
+    }
+
+    /// makes the panel `width` columns wide, border included, instead of
+    /// fitting the text
+    ///
+    /// text that doesn't fit still widens the panel
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// fits the panel to its text again, the default
+    pub fn fit(mut self) -> Self {
+        self.width = None;
+        self
+    }
+
+    /// where lines narrower than the widest one sit
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// a top or bottom edge with an optional label, `None` when the edge
+    /// isn't drawn at all
+    fn edge(
+        &self,
+        label: Option<(&str, Align)>,
+        sides: Sides,
+        edge: Edge,
+        inner: usize,
+    ) -> Option<String> {
+        let on = sides.contains(edge.side);
+        let label = label.filter(|(text, _)| !text.is_empty());
+        if !on && label.is_none() {
+            return None;
+        }
+
+        let fill = if on { edge.line } else { ' ' };
+        let paint = |c: char, n: usize| {
+            self.border_style
+                .paint(&c.to_string().repeat(n))
+                .to_string()
+        };
+        let mut row = String::new();
+        if sides.contains(Sides::LEFT) {
+            row.push_str(&paint(if on { edge.left } else { ' ' }, 1));
+        }
+        match label {
+            Some((text, align)) => {
+                // one line on either side of the label, the rest goes
+                // where `align` says
+                let text = format!(" {} ", text);
+                let gap = inner - display_width(&text) - 2;
+                let before = 1 + match align {
+                    Align::Left => 0,
+                    Align::Center => gap / 2,
+                    Align::Right => gap,
+                };
+                row.push_str(&paint(fill, before));
+                row.push_str(&text);
+                row.push_str(&paint(fill, gap + 2 - before));
+            }
+            None => row.push_str(&paint(fill, inner)),
+        }
+        if sides.contains(Sides::RIGHT) {
+            row.push_str(&paint(if on { edge.right } else { ' ' }, 1));
+        }
+        Some(row)
+    }
+}
+
+/// the characters of a top or bottom edge
+struct Edge {
+    side: Sides,
+    left: char,
+    line: char,
+    right: char,
+}
+
+impl Display for Panel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let chars = self.border.chars();
+        let sides = match self.border {
+            Border::None => Sides::NONE,
+            _ => self.sides,
+        };
+        let (pad_x, pad_y) = self.padding;
+        let (margin_x, margin_y) = self.margin;
+        let lines = ansi::lines(&self.content);
+
+        // labels need a space and a line on either side
+        let label_width = |label: &Option<String>| match label.as_deref() {
+            Some(text) if !text.is_empty() => display_width(text) + 4,
+            _ => 0,
+        };
+        let content_width = lines.iter().map(|l| display_width(l)).max().unwrap_or(0);
+        let frame =
+            usize::from(sides.contains(Sides::LEFT)) + usize::from(sides.contains(Sides::RIGHT));
+        let inner = (content_width + 2 * pad_x)
+            .max(label_width(&self.title))
+            .max(label_width(&self.footer))
+            .max(self.width.map_or(0, |w| w.saturating_sub(frame)));
+        let text_width = inner - 2 * pad_x;
+
+        let mut rows = Vec::new();
+        let top = Edge {
+            side: Sides::TOP,
+            left: chars.top_left,
+            line: chars.top,
+            right: chars.top_right,
+        };
+        let title = self.title.as_deref().map(|t| (t, self.title_align));
+        rows.extend(self.edge(title, sides, top, inner));
+
+        let paint = |c: char| self.border_style.paint(&c).to_string();
+        let left = if sides.contains(Sides::LEFT) {
+            paint(chars.left)
+        } else {
+            String::new()
+        };
+        let right = if sides.contains(Sides::RIGHT) {
+            paint(chars.right)
+        } else {
+            String::new()
+        };
+        let blank = format!("{}{}{}", left, " ".repeat(inner), right);
+        let padding = " ".repeat(pad_x);
+        rows.extend(std::iter::repeat_n(blank.clone(), pad_y));
+        for line in &lines {
+            let text = self.align.pad(line, text_width);
+            rows.push(format!("{}{}{}{}{}", left, padding, text, padding, right));
+        }
+        rows.extend(std::iter::repeat_n(blank, pad_y));
+
+        let bottom = Edge {
+            side: Sides::BOTTOM,
+            left: chars.bottom_left,
+            line: chars.bottom,
+            right: chars.bottom_right,
+        };
+        let footer = self.footer.as_deref().map(|t| (t, self.footer_align));
+        rows.extend(self.edge(footer, sides, bottom, inner));
+
+        let margin = " ".repeat(margin_x);
+        let outside = " ".repeat(inner + frame + 2 * margin_x);
+        let rows = std::iter::repeat_n(outside.clone(), margin_y)
+            .chain(
+                rows.iter()
+                    .map(|row| format!("{}{}{}", margin, row, margin)),
+            )
+            .chain(std::iter::repeat_n(outside, margin_y));
+        for (i, row) in rows.enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            f.write_str(&row)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::term::{with_color_choice, ColorChoice};
+
+    #[test]
+    fn margin_surrounds_the_panel() {
+        let panel = Panel::new("hi").margin(2, 1);
+        let drawn = with_color_choice(ColorChoice::Never, || panel.to_string());
+        assert_eq!(drawn, "        \n  ┌──┐  \n  │hi│  \n  └──┘  \n        ");
+    }
+}
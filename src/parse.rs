@@ -0,0 +1,577 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::color::{Color, NamedColor};
+use crate::space;
+
+/// error returned when a string can't be parsed into a [`Color`]
+///
+/// offsets are byte offsets into the string that was parsed, so they can
+/// be used to point at the offending part of a theme file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColorParseError {
+    /// the input was empty or only whitespace
+    Empty,
+    /// a hex color had the wrong number of digits
+    InvalidLength { offset: usize, found: usize },
+    /// a hex color contained something other than a hex digit
+    InvalidHexDigit { offset: usize, found: char },
+    /// a function argument wasn't a number
+    InvalidNumber { offset: usize, len: usize },
+    /// a function argument was a number outside of the allowed range
+    OutOfRange { offset: usize, len: usize },
+    /// a function call was missing its closing parenthesis
+    MissingParen { offset: usize },
+    /// the color or function name isn't known
+    UnknownName { offset: usize, name: String },
+    /// a function was called with the wrong number of arguments
+    WrongArity {
+        offset: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl ColorParseError {
+    /// byte offset of the error in the parsed string, if it has one
+    pub fn offset(&self) -> Option<usize> {
+        match *self {
+            ColorParseError::Empty => None,
+            ColorParseError::InvalidLength { offset, .. }
+            | ColorParseError::InvalidHexDigit { offset, .. }
+            | ColorParseError::InvalidNumber { offset, .. }
+            | ColorParseError::OutOfRange { offset, .. }
+            | ColorParseError::MissingParen { offset }
+            | ColorParseError::UnknownName { offset, .. }
+            | ColorParseError::WrongArity { offset, .. } => Some(offset),
+        }
+    }
+
+    /// moves the offset by `by` bytes, for colors parsed out of a longer
+    /// string
+    pub(crate) fn shifted(mut self, by: usize) -> Self {
+        match &mut self {
+            ColorParseError::Empty => {}
+            ColorParseError::InvalidLength { offset, .. }
+            | ColorParseError::InvalidHexDigit { offset, .. }
+            | ColorParseError::InvalidNumber { offset, .. }
+            | ColorParseError::OutOfRange { offset, .. }
+            | ColorParseError::MissingParen { offset }
+            | ColorParseError::UnknownName { offset, .. }
+            | ColorParseError::WrongArity { offset, .. } => *offset += by,
+        }
+        self
+    }
+}
+
+impl fmt::Display for ColorParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColorParseError::Empty => f.write_str("empty color"),
+            ColorParseError::InvalidLength { offset, found } => write!(
+                f,
+                "hex color at byte {} has {} digits, expected 3, 4, 6 or 8",
+                offset, found
+            ),
+            ColorParseError::InvalidHexDigit { offset, found } => {
+                write!(f, "invalid hex digit {:?} at byte {}", found, offset)
+            }
+            ColorParseError::InvalidNumber { offset, len } => {
+                write!(f, "invalid number at bytes {}..{}", offset, offset + len)
+            }
+            ColorParseError::OutOfRange { offset, len } => {
+                write!(
+                    f,
+                    "value out of range at bytes {}..{}",
+                    offset,
+                    offset + len
+                )
+            }
+            ColorParseError::MissingParen { offset } => {
+                write!(f, "missing closing parenthesis at byte {}", offset)
+            }
+            ColorParseError::UnknownName { offset, name } => {
+                write!(f, "unknown color {:?} at byte {}", name, offset)
+            }
+            ColorParseError::WrongArity {
+                offset,
+                expected,
+                found,
+            } => write!(
+                f,
+                "function at byte {} takes {} arguments, found {}",
+                offset, expected, found
+            ),
+        }
+    }
+}
+
+impl Error for ColorParseError {}
+
+/// color parser with settings that plain `str::parse` can't take
+///
+/// colors with an alpha channel (`#ff880080`, `rgb(0 0 0 / 50%)`,
+/// `transparent`, ...) are composited against the parser's background,
+/// which is black unless changed
+///
+/// example:
+/// ```rs
+/// let parser = ColorParser::new().background("#ffffff".parse()?);
+/// let c = parser.parse("hsl(210 40% 50% / 0.5)")?;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorParser {
+    background: Color,
+}
+
+impl Default for ColorParser {
+    fn default() -> Self {
+        ColorParser {
+            background: Color::Rgb(0, 0, 0),
+        }
+    }
+}
+
+impl ColorParser {
+    /// creates a parser compositing against black
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// sets the color translucent colors are composited against
+    pub fn background(mut self, color: Color) -> Self {
+        self.background = color;
+        self
+    }
+
+    /// parses a color
+    ///
+    /// accepts
+    /// - hex colors: `#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa` and bare `rrggbb`
+    /// - `rgb()` / `rgba()`, `hsl()` / `hsla()`, `hwb()`, `lab()`, `lch()`,
+    ///   `oklab()` and `oklch()` in both the comma and the space separated
+    ///   syntax, with an optional alpha
+    /// - `hsv()` / `hsva()`, using the same syntax as `hsl()`
+    /// - `ansi16(n)` and `ansi256(n)` terminal palette colors
+    /// - palette names such as `red`, `blue_bright` or `text_blue_500`,
+    ///   css named colors such as `rebeccapurple`, `transparent`, and
+    ///   `default` for the terminal's own color
+    pub fn parse(&self, input: &str) -> Result<Color, ColorParseError> {
+        let (color, alpha) = parse(input)?;
+        if alpha >= 1.0 {
+            return Ok(color);
+        }
+
+        let (r, g, b) = color.to_rgb();
+        let (br, bg, bb) = self.background.to_rgb();
+        let mix =
+            |c: u8, under: u8| (c as f32 * alpha + under as f32 * (1.0 - alpha)).round() as u8;
+        Ok(Color::Rgb(mix(r, br), mix(g, bg), mix(b, bb)))
+    }
+}
+
+impl FromStr for Color {
+    type Err = ColorParseError;
+
+    /// parses a color with the default [`ColorParser`]
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        ColorParser::new().parse(input)
+    }
+}
+
+/// parses `input` into a color and its alpha
+fn parse(input: &str) -> Result<(Color, f32), ColorParseError> {
+    let start = input.len() - input.trim_start().len();
+    let s = input.trim();
+    if s.is_empty() {
+        return Err(ColorParseError::Empty);
+    }
+
+    if let Some(hex) = s.strip_prefix('#') {
+        return parse_hex(hex, start + 1);
+    }
+    if let Some(open) = s.find('(') {
+        return parse_function(s, start, open);
+    }
+    if s.eq_ignore_ascii_case("transparent") {
+        return Ok((Color::Rgb(0, 0, 0), 0.0));
+    }
+    if s.eq_ignore_ascii_case("default") {
+        return Ok((Color::Default, 1.0));
+    }
+    if let Some(named) = NamedColor::from_name(s) {
+        return Ok((Color::Named(named), 1.0));
+    }
+    if s.len() == 6 && s.chars().all(|c| c.is_ascii_hexdigit()) {
+        return parse_hex(s, start);
+    }
+
+    Err(ColorParseError::UnknownName {
+        offset: start,
+        name: s.to_string(),
+    })
+}
+
+fn parse_hex(hex: &str, offset: usize) -> Result<(Color, f32), ColorParseError> {
+    if let Some((i, c)) = hex.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+        return Err(ColorParseError::InvalidHexDigit {
+            offset: offset + i,
+            found: c,
+        });
+    }
+
+    // all digits are ascii at this point, so slicing is safe
+    let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).unwrap_or_default();
+    let pair = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap_or_default();
+
+    let (r, g, b, a) = match hex.len() {
+        3 => (digit(0) * 17, digit(1) * 17, digit(2) * 17, 255),
+        4 => (digit(0) * 17, digit(1) * 17, digit(2) * 17, digit(3) * 17),
+        6 => (pair(0), pair(2), pair(4), 255),
+        8 => (pair(0), pair(2), pair(4), pair(6)),
+        found => return Err(ColorParseError::InvalidLength { offset, found }),
+    };
+
+    Ok((Color::Rgb(r, g, b), a as f32 / 255.0))
+}
+
+/// how a function argument is interpreted
+#[derive(Clone, Copy)]
+enum Component {
+    /// rgb channel, `0..=255` or a percentage, scaled to `0.0..=1.0`
+    Channel,
+    /// alpha, `0..=1` or a percentage
+    Alpha,
+    /// hue in degrees, or any angle unit
+    Hue,
+    /// saturation, lightness, whiteness, ..., a percentage or `0..=100`,
+    /// scaled to `0.0..=1.0`
+    Percent,
+    /// lab / lch lightness, `0..=100`
+    LabLightness,
+    /// unbounded number, percentages are relative to the given reference
+    Axis(f32),
+    /// non-negative chroma, percentages are relative to the given reference
+    Chroma(f32),
+    /// oklab / oklch lightness, `0..=1` or a percentage
+    OkLightness,
+}
+
+/// a single function argument with its position in the input
+struct Arg<'a> {
+    text: &'a str,
+    offset: usize,
+}
+
+impl Arg<'_> {
+    fn invalid(&self) -> ColorParseError {
+        ColorParseError::InvalidNumber {
+            offset: self.offset,
+            len: self.text.len(),
+        }
+    }
+
+    fn out_of_range(&self) -> ColorParseError {
+        ColorParseError::OutOfRange {
+            offset: self.offset,
+            len: self.text.len(),
+        }
+    }
+
+    /// plain integer argument, used by the ansi functions
+    fn number(&self, max: u32) -> Result<u32, ColorParseError> {
+        let digits = !self.text.is_empty() && self.text.chars().all(|c| c.is_ascii_digit());
+        if !digits {
+            return Err(self.invalid());
+        }
+
+        match self.text.parse::<u32>() {
+            Ok(n) if n <= max => Ok(n),
+            _ => Err(self.out_of_range()),
+        }
+    }
+
+    fn float(&self, text: &str) -> Result<f32, ColorParseError> {
+        match text.parse::<f32>() {
+            Ok(v) if v.is_finite() => Ok(v),
+            _ => Err(self.invalid()),
+        }
+    }
+
+    fn component(&self, kind: Component) -> Result<f32, ColorParseError> {
+        if self.text.eq_ignore_ascii_case("none") {
+            return Ok(0.0);
+        }
+
+        let in_range = |v: f32, min: f32, max: f32| {
+            if (min..=max).contains(&v) {
+                Ok(v)
+            } else {
+                Err(self.out_of_range())
+            }
+        };
+
+        if let Some(p) = self.text.strip_suffix('%') {
+            let p = in_range(self.float(p)?, 0.0, 100.0)? / 100.0;
+            return match kind {
+                Component::Channel | Component::Alpha | Component::Percent => Ok(p),
+                Component::OkLightness => Ok(p),
+                Component::LabLightness => Ok(p * 100.0),
+                Component::Axis(reference) | Component::Chroma(reference) => Ok(p * reference),
+                Component::Hue => Err(self.invalid()),
+            };
+        }
+
+        if let Component::Hue = kind {
+            let units = [
+                ("deg", 1.0),
+                ("grad", 0.9),
+                ("rad", 57.295_78),
+                ("turn", 360.0),
+            ];
+            for (unit, scale) in units {
+                if let Some(v) = self.text.strip_suffix(unit) {
+                    return Ok(self.float(v)? * scale);
+                }
+            }
+            return self.float(self.text);
+        }
+
+        let v = self.float(self.text)?;
+        match kind {
+            Component::Channel => Ok(in_range(v, 0.0, 255.0)? / 255.0),
+            Component::Alpha => in_range(v, 0.0, 1.0),
+            Component::Percent => Ok(in_range(v, 0.0, 100.0)? / 100.0),
+            Component::LabLightness => in_range(v, 0.0, 100.0),
+            Component::OkLightness => in_range(v, 0.0, 1.0),
+            Component::Axis(_) => Ok(v),
+            Component::Chroma(_) => in_range(v, 0.0, f32::MAX),
+            Component::Hue => unreachable!(),
+        }
+    }
+}
+
+/// splits `body` into its components and optional alpha, supporting both
+/// `a, b, c[, alpha]` and `a b c[ / alpha]`
+fn split_args(body: &str, offset: usize) -> (Vec<Arg<'_>>, Option<Arg<'_>>) {
+    let to_arg = |range: (usize, usize)| {
+        let raw = &body[range.0..range.1];
+        let lead = raw.len() - raw.trim_start().len();
+        Arg {
+            text: raw.trim(),
+            offset: offset + range.0 + lead,
+        }
+    };
+
+    if body.contains(',') {
+        let mut args = Vec::new();
+        let mut start = 0;
+        for part in body.split(',') {
+            args.push(to_arg((start, start + part.len())));
+            start += part.len() + 1;
+        }
+        let alpha = if args.len() == 4 { args.pop() } else { None };
+        return (args, alpha);
+    }
+
+    let (components, alpha) = match body.find('/') {
+        Some(slash) => (&body[..slash], Some(slash)),
+        None => (body, None),
+    };
+
+    let mut args = Vec::new();
+    let mut begin = None;
+    for (i, c) in components.char_indices() {
+        match (c.is_whitespace(), begin) {
+            (true, Some(b)) => {
+                args.push(to_arg((b, i)));
+                begin = None;
+            }
+            (false, None) => begin = Some(i),
+            _ => {}
+        }
+    }
+    if let Some(b) = begin {
+        args.push(to_arg((b, components.len())));
+    }
+
+    let alpha = alpha.map(|slash| to_arg((slash + 1, body.len())));
+    (args, alpha)
+}
+
+fn parse_function(s: &str, offset: usize, open: usize) -> Result<(Color, f32), ColorParseError> {
+    let name = s[..open].trim_end().to_ascii_lowercase();
+    let body = match s[open + 1..].strip_suffix(')') {
+        Some(body) => body,
+        None => {
+            return Err(ColorParseError::MissingParen {
+                offset: offset + s.len(),
+            })
+        }
+    };
+    let body_offset = offset + open + 1;
+
+    if name == "ansi16" || name == "ansi256" {
+        let lead = body.len() - body.trim_start().len();
+        let arg = Arg {
+            text: body.trim(),
+            offset: body_offset + lead,
+        };
+        if arg.text.contains(|c: char| c == ',' || c.is_whitespace()) {
+            let found = arg.text.split([',', ' ']).filter(|p| !p.is_empty()).count();
+            return Err(ColorParseError::WrongArity {
+                offset,
+                expected: 1,
+                found,
+            });
+        }
+        return Ok(match name.as_str() {
+            "ansi16" => (Color::Ansi16(arg.number(15)? as u8), 1.0),
+            _ => (Color::Ansi256(arg.number(255)? as u8), 1.0),
+        });
+    }
+
+    use Component::*;
+    let kinds = match name.as_str() {
+        "rgb" | "rgba" => [Channel, Channel, Channel],
+        "hsl" | "hsla" | "hsv" | "hsva" | "hwb" => [Hue, Percent, Percent],
+        "lab" => [LabLightness, Axis(125.0), Axis(125.0)],
+        "lch" => [LabLightness, Chroma(150.0), Hue],
+        "oklab" => [OkLightness, Axis(0.4), Axis(0.4)],
+        "oklch" => [OkLightness, Chroma(0.4), Hue],
+        _ => {
+            return Err(ColorParseError::UnknownName {
+                offset,
+                name: s[..open].trim_end().to_string(),
+            })
+        }
+    };
+
+    let (args, alpha) = split_args(body, body_offset);
+    if args.len() != 3 {
+        return Err(ColorParseError::WrongArity {
+            offset,
+            expected: 3,
+            found: args.len() + alpha.is_some() as usize,
+        });
+    }
+
+    let mut values = [0.0; 3];
+    for (value, (arg, kind)) in values.iter_mut().zip(args.iter().zip(kinds)) {
+        *value = arg.component(kind)?;
+    }
+    let alpha = match alpha {
+        Some(arg) => arg.component(Alpha)?,
+        None => 1.0,
+    };
+
+    let [a, b, c] = values;
+    let rgb = match name.as_str() {
+        "rgb" | "rgba" => values,
+        "hsl" | "hsla" => space::hsl_to_rgb(a, b, c),
+        "hsv" | "hsva" => space::hsv_to_rgb(a, b, c),
+        "hwb" => space::hwb_to_rgb(a, b, c),
+        "lab" => space::lab_to_linear(values).map(space::linear_to_srgb),
+        "lch" => space::lab_to_linear(space::polar_to_rect(a, b, c)).map(space::linear_to_srgb),
+        "oklab" => space::oklab_to_linear(values).map(space::linear_to_srgb),
+        _ => space::oklab_to_linear(space::polar_to_rect(a, b, c)).map(space::linear_to_srgb),
+    };
+
+    let (r, g, b) = space::to_bytes(rgb);
+    Ok((Color::Rgb(r, g, b), alpha))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn err(input: &str) -> ColorParseError {
+        input.parse::<Color>().unwrap_err()
+    }
+
+    #[test]
+    fn parses_every_syntax() {
+        let parse = |s: &str| s.parse::<Color>().unwrap();
+        assert_eq!(parse("#ff8800"), Color::Rgb(255, 136, 0));
+        assert_eq!(parse("#f80"), Color::Rgb(255, 136, 0));
+        assert_eq!(parse("ff8800"), Color::Rgb(255, 136, 0));
+        assert_eq!(parse("rgb(255, 128, 0)"), Color::Rgb(255, 128, 0));
+        assert_eq!(parse("rgb(255 128 0 / 50%)"), Color::Rgb(128, 64, 0));
+        assert_eq!(parse("hsl(0 100% 50%)"), Color::Rgb(255, 0, 0));
+        assert_eq!(parse("ansi256(42)"), Color::Ansi256(42));
+        assert_eq!(parse(" text_blue_500 ").name(), Some("blue_500"));
+        assert_eq!(parse("default"), Color::Default);
+        assert_eq!(Color::Default.to_string(), "default");
+    }
+
+    #[test]
+    fn translucent_colors_use_the_background() {
+        let white = Color::Rgb(255, 255, 255);
+        assert_eq!("#ffffff80".parse(), Ok(Color::Rgb(128, 128, 128)));
+        assert_eq!(
+            ColorParser::new().background(white).parse("#ffffff80"),
+            Ok(white)
+        );
+        assert_eq!(
+            ColorParser::new().background(white).parse("transparent"),
+            Ok(white)
+        );
+    }
+
+    #[test]
+    fn errors_point_into_the_input() {
+        assert_eq!(err("  "), ColorParseError::Empty);
+        assert_eq!(
+            err("  #12g"),
+            ColorParseError::InvalidHexDigit {
+                offset: 5,
+                found: 'g'
+            }
+        );
+        assert_eq!(
+            err("#12345"),
+            ColorParseError::InvalidLength {
+                offset: 1,
+                found: 5
+            }
+        );
+        assert_eq!(
+            err("rgb(1, 2, x)"),
+            ColorParseError::InvalidNumber { offset: 10, len: 1 }
+        );
+        assert_eq!(
+            err("rgb(300 0 0)"),
+            ColorParseError::OutOfRange { offset: 4, len: 3 }
+        );
+        assert_eq!(
+            err("ansi16(16)"),
+            ColorParseError::OutOfRange { offset: 7, len: 2 }
+        );
+        assert_eq!(
+            err("rgb(1 2 3"),
+            ColorParseError::MissingParen { offset: 9 }
+        );
+        assert_eq!(
+            err(" nope"),
+            ColorParseError::UnknownName {
+                offset: 1,
+                name: "nope".to_string()
+            }
+        );
+        assert_eq!(
+            err("rgb(1 2)"),
+            ColorParseError::WrongArity {
+                offset: 0,
+                expected: 3,
+                found: 2
+            }
+        );
+    }
+
+    #[test]
+    fn shifted_moves_the_offset() {
+        assert_eq!(err("#12g").shifted(4).offset(), Some(7));
+        assert_eq!(err("").shifted(4).offset(), None);
+    }
+}
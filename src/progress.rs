@@ -0,0 +1,814 @@
+use std::fmt::{self, Display, Write as _};
+use std::io::{self, IsTerminal, Write};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::color::Color;
+use crate::gradient::Gradient;
+use crate::style::Style;
+use crate::term;
+use crate::width::display_width;
+
+/// the characters a [`ProgressBar`] is drawn with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BarFill {
+    /// full and light shade blocks, `████░░░░`
+    #[default]
+    Blocks,
+    /// full blocks with an eighth block at the edge, so the bar moves in
+    /// steps of an eighth of a cell, `███▍    `
+    Eighths,
+    /// plain ascii for terminals without block characters, `####----`
+    Ascii,
+    /// any two characters for the done and the remaining part
+    Chars { full: char, empty: char },
+}
+
+const EIGHTHS: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+impl BarFill {
+    fn full(self) -> char {
+        match self {
+            BarFill::Blocks | BarFill::Eighths => '█',
+            BarFill::Ascii => '#',
+            BarFill::Chars { full, .. } => full,
+        }
+    }
+
+    fn empty(self) -> char {
+        match self {
+            BarFill::Blocks => '░',
+            BarFill::Eighths => ' ',
+            BarFill::Ascii => '-',
+            BarFill::Chars { empty, .. } => empty,
+        }
+    }
+}
+
+/// how the done part of a [`ProgressBar`] is colored
+///
+/// example:
+/// ```rs
+/// // the whole bar turns from red to green as it fills
+/// let color = BarColor::Thresholds(vec![
+///     (0.0, "red".parse()?),
+///     (0.5, "yellow".parse()?),
+///     (0.9, "green".parse()?),
+/// ]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum BarColor {
+    /// left as it is
+    #[default]
+    None,
+    /// one color
+    Solid(Color),
+    /// a gradient laid over the full width of the bar, the done part shows
+    /// as much of it as it covers
+    Gradient(Gradient),
+    /// `(fraction, color)` pairs, the bar takes the color of the last pair
+    /// the progress has reached
+    Thresholds(Vec<(f32, Color)>),
+}
+
+impl BarColor {
+    /// color of the cell at `index` of a bar `width` cells wide that is
+    /// `fraction` done
+    fn at(&self, index: usize, width: usize, fraction: f32) -> Option<Color> {
+        match self {
+            BarColor::None => None,
+            BarColor::Solid(color) => Some(*color),
+            BarColor::Gradient(gradient) => {
+                let last = width.saturating_sub(1).max(1) as f32;
+                Some(gradient.sample(index as f32 / last))
+            }
+            BarColor::Thresholds(steps) => steps
+                .iter()
+                .take_while(|(at, _)| *at <= fraction)
+                .last()
+                .or(steps.first())
+                .map(|(_, color)| *color),
+        }
+    }
+}
+
+/// a progress bar drawn in place on one line
+///
+/// a bar is a handle, clones share the same progress so it can be updated
+/// from other threads. redraws are throttled, finishing always draws.
+/// escapes are only written when colors are enabled, otherwise the line is
+/// redrawn with a plain carriage return
+///
+/// whether colors are enabled is decided for the output the bar is drawn
+/// to, stderr unless changed, which is checked for a terminal. other
+/// outputs are taken as not being one unless [`ProgressBar::terminal`]
+/// says so
+///
+/// the line is laid out by a template with these fields:
+///
+/// - `{bar}` the bar itself
+/// - `{percent}` how much is done, `42%`
+/// - `{pos}` and `{len}` the position and the length
+/// - `{rate}` steps per second, `12.5/s`
+/// - `{eta}` time left, `1m05s`
+/// - `{elapsed}` time since the bar was created
+/// - `{prefix}` and `{msg}` text set with `set_prefix` and `set_message`
+///
+/// example:
+/// ```rs
+/// let bar = ProgressBar::new(files.len() as u64)
+///     .fill(BarFill::Eighths)
+///     .color(BarColor::Gradient(Gradient::parse(&["#ff0080", "#0070f3"])?))
+///     .template("{prefix} │{bar}│ {percent} {rate} eta {eta} {msg}");
+/// bar.set_prefix("copying");
+/// for file in files {
+///     bar.set_message(&file);
+///     copy(&file)?;
+///     bar.inc(1);
+/// }
+/// bar.finish();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProgressBar {
+    state: Arc<Mutex<State>>,
+}
+
+struct State {
+    len: Option<u64>,
+    pos: u64,
+    prefix: String,
+    message: String,
+    width: usize,
+    fill: BarFill,
+    color: BarColor,
+    empty_style: Style,
+    template: String,
+    throttle: Duration,
+    start: Instant,
+    last_draw: Option<Instant>,
+    // redraws so far, moves the indeterminate bar
+    ticks: u64,
+    finished: bool,
+    target: Target,
+}
+
+/// where a bar is drawn
+enum Target {
+    /// on its own line of an output
+    Line(Line),
+    /// as one of the bars of a [`MultiProgress`], by id
+    Multi(Arc<Mutex<Multi>>, usize),
+}
+
+/// an output a single line is redrawn on
+struct Line {
+    out: Box<dyn Write + Send>,
+    terminal: bool,
+    // columns of the line last drawn, to clear it without escapes
+    drawn: usize,
+}
+
+impl fmt::Debug for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("State")
+            .field("len", &self.len)
+            .field("pos", &self.pos)
+            .field("message", &self.message)
+            .field("finished", &self.finished)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ProgressBar {
+    /// a bar that is done after `len` steps, drawn to stderr
+    pub fn new(len: u64) -> Self {
+        Self::with_len(Some(len), "{bar} {percent}")
+    }
+
+    /// a bar for work of unknown length, a block bounces back and forth
+    /// instead of filling the bar
+    pub fn indeterminate() -> Self {
+        Self::with_len(None, "{bar} {elapsed}")
+    }
+
+    fn with_len(len: Option<u64>, template: &str) -> Self {
+        let state = State {
+            len,
+            pos: 0,
+            prefix: String::new(),
+            message: String::new(),
+            width: 30,
+            fill: BarFill::default(),
+            color: BarColor::default(),
+            empty_style: Style::new(),
+            template: template.to_string(),
+            throttle: Duration::from_millis(50),
+            start: Instant::now(),
+            last_draw: None,
+            ticks: 0,
+            finished: false,
+            target: Target::Line(Line::stderr()),
+        };
+        ProgressBar {
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        lock(&self.state)
+    }
+
+    fn configure(self, f: impl FnOnce(&mut State)) -> Self {
+        f(&mut self.lock());
+        self
+    }
+
+    /// cells the bar itself takes up, 30 by default
+    pub fn width(self, width: usize) -> Self {
+        self.configure(|s| s.width = width)
+    }
+
+    /// the characters the bar is drawn with
+    pub fn fill(self, fill: BarFill) -> Self {
+        self.configure(|s| s.fill = fill)
+    }
+
+    /// how the done part of the bar is colored
+    pub fn color(self, color: BarColor) -> Self {
+        self.configure(|s| s.color = color)
+    }
+
+    /// the style of the remaining part of the bar
+    pub fn empty_style(self, style: Style) -> Self {
+        self.configure(|s| s.empty_style = style)
+    }
+
+    /// how the line is laid out, see [`ProgressBar`] for the fields
+    pub fn template(self, template: impl Into<String>) -> Self {
+        self.configure(|s| s.template = template.into())
+    }
+
+    /// the least time between two redraws, 50ms by default
+    pub fn throttle(self, interval: Duration) -> Self {
+        self.configure(|s| s.throttle = interval)
+    }
+
+    /// where the bar is drawn, stderr by default, taken as not being a
+    /// terminal
+    pub fn output(self, out: impl Write + Send + 'static) -> Self {
+        self.configure(|s| s.target = Target::Line(Line::new(out, false)))
+    }
+
+    /// whether the output set with [`ProgressBar::output`] is a terminal,
+    /// which redraws the line in place and colors it under
+    /// `ColorChoice::Auto`
+    pub fn terminal(self, terminal: bool) -> Self {
+        self.configure(|s| {
+            if let Target::Line(line) = &mut s.target {
+                line.terminal = terminal;
+            }
+        })
+    }
+
+    /// steps done so far
+    pub fn position(&self) -> u64 {
+        self.lock().pos
+    }
+
+    /// steps in total, `None` for an indeterminate bar
+    pub fn length(&self) -> Option<u64> {
+        self.lock().len
+    }
+
+    /// whether one of the `finish` methods was called
+    pub fn is_finished(&self) -> bool {
+        self.lock().finished
+    }
+
+    /// moves the bar `delta` steps on
+    pub fn inc(&self, delta: u64) {
+        let mut state = self.lock();
+        state.pos = state.pos.saturating_add(delta);
+        state.draw(false);
+    }
+
+    /// moves the bar to `pos`
+    pub fn set_position(&self, pos: u64) {
+        let mut state = self.lock();
+        state.pos = pos;
+        state.draw(false);
+    }
+
+    /// changes the number of steps, `None` makes the bar indeterminate
+    pub fn set_length(&self, len: Option<u64>) {
+        let mut state = self.lock();
+        state.len = len;
+        state.draw(false);
+    }
+
+    /// text for the `{prefix}` field
+    pub fn set_prefix(&self, prefix: impl Display) {
+        let mut state = self.lock();
+        state.prefix = prefix.to_string();
+        state.draw(false);
+    }
+
+    /// text for the `{msg}` field
+    pub fn set_message(&self, message: impl Display) {
+        let mut state = self.lock();
+        state.message = message.to_string();
+        state.draw(false);
+    }
+
+    /// redraws without progress, which keeps an indeterminate bar moving
+    pub fn tick(&self) {
+        self.lock().draw(false);
+    }
+
+    /// fills the bar, draws it a last time and moves to the next line
+    pub fn finish(&self) {
+        let mut state = self.lock();
+        if let Some(len) = state.len {
+            state.pos = len;
+        }
+        state.finish(true);
+    }
+
+    /// like [`finish`](ProgressBar::finish) with a last message
+    pub fn finish_with_message(&self, message: impl Display) {
+        let mut state = self.lock();
+        state.message = message.to_string();
+        if let Some(len) = state.len {
+            state.pos = len;
+        }
+        state.finish(true);
+    }
+
+    /// stops where the bar is and removes it from the screen
+    pub fn finish_and_clear(&self) {
+        self.lock().finish(false);
+    }
+
+    /// the line as it would be drawn now, without moving the cursor
+    pub fn render(&self) -> String {
+        self.lock().render()
+    }
+}
+
+impl State {
+    fn fraction(&self) -> f32 {
+        match self.len {
+            Some(0) => 1.0,
+            Some(len) => (self.pos as f64 / len as f64).clamp(0.0, 1.0) as f32,
+            None => 0.0,
+        }
+    }
+
+    /// the line with colors as the target gets them
+    fn render_for_target(&self) -> String {
+        let colors = match &self.target {
+            Target::Line(line) => line.colors(),
+            Target::Multi(multi, _) => lock(multi).colors(),
+        };
+        with_colors(colors, || self.render())
+    }
+
+    fn render(&self) -> String {
+        let mut line = String::new();
+        let mut rest = self.template.as_str();
+        while let Some(open) = rest.find('{') {
+            line.push_str(&rest[..open]);
+            let field = rest[open + 1..]
+                .find('}')
+                .map(|close| &rest[open + 1..open + 1 + close]);
+            match field.and_then(|name| self.field(name)) {
+                Some(value) => {
+                    line.push_str(&value);
+                    rest = &rest[open + field.map_or(0, str::len) + 2..];
+                }
+                // unknown fields are kept as they are
+                None => {
+                    line.push('{');
+                    rest = &rest[open + 1..];
+                }
+            }
+        }
+        line.push_str(rest);
+        line
+    }
+
+    fn field(&self, name: &str) -> Option<String> {
+        let elapsed = self.start.elapsed();
+        let rate = self.pos as f64 / elapsed.as_secs_f64().max(0.001);
+        let value = match name {
+            "bar" => self.bar(),
+            "percent" => match self.len {
+                Some(_) => format!("{:>3}%", (self.fraction() * 100.0).floor() as u32),
+                None => String::new(),
+            },
+            "pos" => self.pos.to_string(),
+            "len" => self.len.map(|len| len.to_string()).unwrap_or_default(),
+            "rate" => format!("{:.1}/s", rate),
+            "eta" => match self.len {
+                Some(len) if rate > 0.0 => {
+                    let left = len.saturating_sub(self.pos) as f64 / rate;
+                    // too far off to be shown when it doesn't fit a duration
+                    Duration::try_from_secs_f64(left)
+                        .map(format_duration)
+                        .unwrap_or_default()
+                }
+                _ => String::new(),
+            },
+            "elapsed" => format_duration(elapsed),
+            "prefix" => self.prefix.clone(),
+            "msg" => self.message.clone(),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    fn bar(&self) -> String {
+        let width = self.width;
+        let fraction = self.fraction();
+        let (full, empty) = (self.fill.full(), self.fill.empty());
+
+        // the cells of the bar, `true` where it is done
+        let mut cells = vec![(empty, false); width];
+        match self.len {
+            Some(_) => {
+                let steps = match self.fill {
+                    BarFill::Eighths => 8,
+                    _ => 1,
+                };
+                let done = (fraction * (width * steps) as f32).floor() as usize;
+                for cell in cells.iter_mut().take(done / steps) {
+                    *cell = (full, true);
+                }
+                if done % steps != 0 {
+                    cells[done / steps] = (EIGHTHS[done % steps], true);
+                }
+            }
+            None => {
+                // a block a fifth of the bar wide bouncing between the ends
+                let size = (width / 5).max(1).min(width);
+                let span = (width - size) as u64;
+                let offset = match span {
+                    0 => 0,
+                    _ => {
+                        let t = self.ticks % (2 * span);
+                        (if t > span { 2 * span - t } else { t }) as usize
+                    }
+                };
+                for cell in cells.iter_mut().skip(offset).take(size) {
+                    *cell = (full, true);
+                }
+            }
+        }
+
+        let mut bar = String::new();
+        let mut run = String::new();
+        let mut run_style = Style::new();
+        for (i, (c, done)) in cells.into_iter().enumerate() {
+            let style = match done {
+                true => match self.color.at(i, width, fraction) {
+                    Some(color) => Style::new().fg(color),
+                    None => Style::new(),
+                },
+                false => self.empty_style,
+            };
+            // cells in the same style are painted together
+            if !run.is_empty() && run_style != style {
+                let _ = write!(bar, "{}", run_style.paint(&run));
+                run.clear();
+            }
+            run_style = style;
+            run.push(c);
+        }
+        let _ = write!(bar, "{}", run_style.paint(&run));
+        bar
+    }
+
+    fn draw(&mut self, force: bool) {
+        if self.finished {
+            return;
+        }
+        let now = Instant::now();
+        let due = self
+            .last_draw
+            .is_none_or(|last| now.duration_since(last) >= self.throttle);
+        if !force && !due {
+            return;
+        }
+        self.last_draw = Some(now);
+        let line = self.render_for_target();
+        self.ticks += 1;
+        match &mut self.target {
+            Target::Line(out) => out.redraw(&line),
+            Target::Multi(multi, id) => lock(multi).update(*id, &line, force),
+        }
+    }
+
+    fn finish(&mut self, keep: bool) {
+        if self.finished {
+            return;
+        }
+        self.finished = true;
+        let line = self.render_for_target();
+        match &mut self.target {
+            Target::Line(out) => out.finish(keep.then_some(line.as_str())),
+            Target::Multi(multi, id) => {
+                let mut multi = lock(multi);
+                match keep {
+                    true => multi.update(*id, &line, true),
+                    false => multi.remove(*id),
+                }
+            }
+        }
+    }
+}
+
+impl Line {
+    fn new(out: impl Write + Send + 'static, terminal: bool) -> Self {
+        Line {
+            out: Box::new(out),
+            terminal,
+            drawn: 0,
+        }
+    }
+
+    fn stderr() -> Self {
+        Line::new(io::stderr(), io::stderr().is_terminal())
+    }
+
+    /// whether escapes are written to the output
+    fn colors(&self) -> bool {
+        term::colors_enabled_for(self.terminal)
+    }
+
+    fn redraw(&mut self, line: &str) {
+        let width = display_width(line);
+        let text = if self.colors() {
+            format!("\r{}\x1B[K", line)
+        } else {
+            // cover what is left of a longer line
+            let clear = " ".repeat(self.drawn.saturating_sub(width));
+            format!("\r{}{}", line, clear)
+        };
+        self.drawn = width;
+        write_out(&mut self.out, &text);
+    }
+
+    /// draws `line` a last time and moves on, or clears the line without it
+    fn finish(&mut self, line: Option<&str>) {
+        match line {
+            Some(line) => {
+                self.redraw(line);
+                write_out(&mut self.out, "\n");
+            }
+            None => {
+                self.redraw("");
+                write_out(&mut self.out, "\r");
+            }
+        }
+    }
+}
+
+/// runs `f` with colors on or off, for rendering lines for an output
+/// other than the color stream
+fn with_colors<R>(colors: bool, f: impl FnOnce() -> R) -> R {
+    let choice = match colors {
+        true => term::ColorChoice::Always,
+        false => term::ColorChoice::Never,
+    };
+    term::with_color_choice(choice, f)
+}
+
+/// progress is only for show, a broken output must not stop the work
+fn write_out(out: &mut Box<dyn Write + Send>, text: &str) {
+    let _ = out.write_all(text.as_bytes());
+    let _ = out.flush();
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // a panic while drawing leaves nothing half updated worth refusing
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// several progress bars drawn together, one per line
+///
+/// bars added to it are drawn below each other and redrawn in place by
+/// moving the cursor up. it's a handle like [`ProgressBar`], bars can be
+/// added, updated and finished from any thread, and lines printed with
+/// [`println`](MultiProgress::println) go above the bars without tearing
+/// them. when colors are off for its output, which is decided like for a
+/// single bar, every change is printed on a line of its own instead
+///
+/// example:
+/// ```rs
+/// let multi = MultiProgress::new();
+/// let handles: Vec<_> = urls.into_iter().map(|url| {
+///     let bar = multi.add(ProgressBar::new(100).template("{bar} {percent} {msg}"));
+///     let multi = multi.clone();
+///     thread::spawn(move || {
+///         bar.set_message(&url);
+///         download(&url, |percent| bar.set_position(percent));
+///         multi.println(format!("fetched {}", url));
+///         bar.finish();
+///     })
+/// }).collect();
+/// ```
+#[derive(Debug, Clone)]
+pub struct MultiProgress {
+    state: Arc<Mutex<Multi>>,
+}
+
+struct Multi {
+    out: Box<dyn Write + Send>,
+    terminal: bool,
+    // the line of every bar, in the order they were added
+    lines: Vec<(usize, String)>,
+    next_id: usize,
+    // bar lines on screen above the cursor
+    drawn: usize,
+    throttle: Duration,
+    last_draw: Option<Instant>,
+}
+
+impl fmt::Debug for Multi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Multi")
+            .field("lines", &self.lines)
+            .field("drawn", &self.drawn)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for MultiProgress {
+    fn default() -> Self {
+        MultiProgress::new()
+    }
+}
+
+impl MultiProgress {
+    /// an empty display drawn to stderr
+    pub fn new() -> Self {
+        let multi = Multi {
+            out: Box::new(io::stderr()),
+            terminal: io::stderr().is_terminal(),
+            lines: Vec::new(),
+            next_id: 0,
+            drawn: 0,
+            throttle: Duration::from_millis(50),
+            last_draw: None,
+        };
+        MultiProgress {
+            state: Arc::new(Mutex::new(multi)),
+        }
+    }
+
+    /// where the bars are drawn, stderr by default, taken as not being a
+    /// terminal
+    pub fn output(self, out: impl Write + Send + 'static) -> Self {
+        {
+            let mut multi = lock(&self.state);
+            multi.out = Box::new(out);
+            multi.terminal = false;
+        }
+        self
+    }
+
+    /// whether the output set with [`MultiProgress::output`] is a terminal,
+    /// which redraws the bars in place and colors them under
+    /// `ColorChoice::Auto`
+    pub fn terminal(self, terminal: bool) -> Self {
+        lock(&self.state).terminal = terminal;
+        self
+    }
+
+    /// the least time between two redraws of all bars, 50ms by default
+    pub fn throttle(self, interval: Duration) -> Self {
+        lock(&self.state).throttle = interval;
+        self
+    }
+
+    /// draws `bar` below the others from now on and gives it back
+    pub fn add(&self, bar: ProgressBar) -> ProgressBar {
+        {
+            let mut state = bar.lock();
+            let mut multi = lock(&self.state);
+            let line = with_colors(multi.colors(), || state.render());
+            let id = multi.next_id;
+            multi.next_id += 1;
+            multi.lines.push((id, String::new()));
+            multi.update(id, &line, true);
+            state.target = Target::Multi(Arc::clone(&self.state), id);
+        }
+        bar
+    }
+
+    /// prints `message` above the bars
+    pub fn println(&self, message: impl Display) {
+        lock(&self.state).println(&message.to_string());
+    }
+
+    /// removes all bars from the screen, bars still running are no longer
+    /// drawn
+    pub fn clear(&self) {
+        let mut multi = lock(&self.state);
+        multi.lines.clear();
+        multi.redraw(true);
+    }
+}
+
+impl Multi {
+    /// whether escapes are written to the output
+    fn colors(&self) -> bool {
+        term::colors_enabled_for(self.terminal)
+    }
+
+    fn update(&mut self, id: usize, line: &str, force: bool) {
+        let Some((_, old)) = self.lines.iter_mut().find(|(i, _)| *i == id) else {
+            return;
+        };
+        let changed = old != line;
+        *old = line.to_string();
+        if self.colors() {
+            // a forced redraw catches up on changes skipped by throttling
+            if changed || force {
+                self.redraw(force);
+            }
+        } else if changed {
+            let text = format!("{}\n", line);
+            write_out(&mut self.out, &text);
+        }
+    }
+
+    fn remove(&mut self, id: usize) {
+        self.lines.retain(|(i, _)| *i != id);
+        self.redraw(true);
+    }
+
+    fn println(&mut self, message: &str) {
+        if !self.colors() {
+            let text = format!("{}\n", message);
+            write_out(&mut self.out, &text);
+            return;
+        }
+        // the message takes the place of the bars, which are drawn again
+        // below it
+        let mut text = self.move_up();
+        for line in message.split('\n') {
+            text.push_str(line);
+            text.push_str("\x1B[K\n");
+        }
+        self.drawn = 0;
+        self.draw_bars(&mut text);
+        write_out(&mut self.out, &text);
+    }
+
+    fn redraw(&mut self, force: bool) {
+        if !self.colors() {
+            return;
+        }
+        let now = Instant::now();
+        let due = self
+            .last_draw
+            .is_none_or(|last| now.duration_since(last) >= self.throttle);
+        if !force && !due {
+            return;
+        }
+        let mut text = self.move_up();
+        self.draw_bars(&mut text);
+        write_out(&mut self.out, &text);
+    }
+
+    /// moves the cursor to the start of the first bar
+    fn move_up(&self) -> String {
+        match self.drawn {
+            0 => "\r".to_string(),
+            n => format!("\r\x1B[{}A", n),
+        }
+    }
+
+    /// every bar on a line of its own, then clears what is left of bars
+    /// drawn before
+    fn draw_bars(&mut self, text: &mut String) {
+        for (_, line) in &self.lines {
+            text.push_str(line);
+            text.push_str("\x1B[K\n");
+        }
+        text.push_str("\x1B[J");
+        self.drawn = self.lines.len();
+        self.last_draw = Some(Instant::now());
+    }
+}
+
+/// `1h02m`, `3m05s` or `12s`
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match (secs / 3600, secs / 60 % 60, secs % 60) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, s) => format!("{}m{:02}s", m, s),
+        (h, m, _) => format!("{}h{:02}m", h, m),
+    }
+}
@@ -0,0 +1,171 @@
+// color space conversions, all channels are f32 with rgb in 0.0..=1.0
+
+/// converts an srgb channel to linear light
+pub(crate) fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// converts a linear light channel back to srgb
+pub(crate) fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// hue in degrees, saturation and lightness in 0.0..=1.0
+pub(crate) fn hsl_to_rgb(h: f32, s: f32, l: f32) -> [f32; 3] {
+    let h = h.rem_euclid(360.0);
+    let channel = |n: f32| {
+        let k = (n + h / 30.0) % 12.0;
+        let a = s * l.min(1.0 - l);
+        l - a * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0)
+    };
+    [channel(0.0), channel(8.0), channel(4.0)]
+}
+
+/// inverse of [`hsl_to_rgb`], the hue of greys is `NaN`
+pub(crate) fn rgb_to_hsl(rgb: [f32; 3]) -> [f32; 3] {
+    let [r, g, b] = rgb;
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = (max + min) / 2.0;
+    let d = max - min;
+
+    if d <= f32::EPSILON {
+        return [f32::NAN, 0.0, l];
+    }
+
+    let s = if l <= 0.0 || l >= 1.0 {
+        0.0
+    } else {
+        (max - l) / l.min(1.0 - l)
+    };
+    let h = if max == r {
+        (g - b) / d + if g < b { 6.0 } else { 0.0 }
+    } else if max == g {
+        (b - r) / d + 2.0
+    } else {
+        (r - g) / d + 4.0
+    };
+
+    [h * 60.0, s, l]
+}
+
+/// hue in degrees, saturation and value in 0.0..=1.0
+pub(crate) fn hsv_to_rgb(h: f32, s: f32, v: f32) -> [f32; 3] {
+    let h = h.rem_euclid(360.0);
+    let channel = |n: f32| {
+        let k = (n + h / 60.0) % 6.0;
+        v - v * s * k.min(4.0 - k).clamp(0.0, 1.0)
+    };
+    [channel(5.0), channel(3.0), channel(1.0)]
+}
+
+/// hue in degrees, whiteness and blackness in 0.0..=1.0
+pub(crate) fn hwb_to_rgb(h: f32, w: f32, b: f32) -> [f32; 3] {
+    if w + b >= 1.0 {
+        let grey = w / (w + b);
+        return [grey; 3];
+    }
+    let rgb = hsl_to_rgb(h, 1.0, 0.5);
+    rgb.map(|c| c * (1.0 - w - b) + w)
+}
+
+/// converts polar lightness/chroma/hue coordinates to rectangular ones
+pub(crate) fn polar_to_rect(l: f32, c: f32, h: f32) -> [f32; 3] {
+    let h = h.to_radians();
+    [l, c * h.cos(), c * h.sin()]
+}
+
+/// inverse of [`polar_to_rect`], the hue of achromatic colors is `NaN`
+pub(crate) fn rect_to_polar(lab: [f32; 3]) -> [f32; 3] {
+    let [l, a, b] = lab;
+    let c = (a * a + b * b).sqrt();
+    let h = if c < 1e-4 {
+        f32::NAN
+    } else {
+        b.atan2(a).to_degrees().rem_euclid(360.0)
+    };
+    [l, c, h]
+}
+
+/// linear srgb to oklab
+pub(crate) fn linear_to_oklab(rgb: [f32; 3]) -> [f32; 3] {
+    let [r, g, b] = rgb;
+    let l = 0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b;
+    let m = 0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b;
+    let s = 0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b;
+
+    let (l, m, s) = (l.cbrt(), m.cbrt(), s.cbrt());
+
+    [
+        0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+        1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+        0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+    ]
+}
+
+/// oklab to linear srgb
+pub(crate) fn oklab_to_linear(lab: [f32; 3]) -> [f32; 3] {
+    let [l, a, b] = lab;
+    let l_ = l + 0.396_337_78 * a + 0.215_803_76 * b;
+    let m_ = l - 0.105_561_346 * a - 0.063_854_17 * b;
+    let s_ = l - 0.089_484_18 * a - 1.291_485_5 * b;
+
+    let (l, m, s) = (l_ * l_ * l_, m_ * m_ * m_, s_ * s_ * s_);
+
+    [
+        4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s,
+        -1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s,
+        -0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s,
+    ]
+}
+
+/// cie lab (d50 white point, as used by css) to linear srgb
+pub(crate) fn lab_to_linear(lab: [f32; 3]) -> [f32; 3] {
+    const K: f32 = 24389.0 / 27.0;
+    const E: f32 = 216.0 / 24389.0;
+    const WHITE: [f32; 3] = [0.964_22, 1.0, 0.825_21];
+
+    let [l, a, b] = lab;
+    let fy = (l + 16.0) / 116.0;
+    let fx = a / 500.0 + fy;
+    let fz = fy - b / 200.0;
+
+    let x = if fx.powi(3) > E {
+        fx.powi(3)
+    } else {
+        (116.0 * fx - 16.0) / K
+    };
+    let y = if l > K * E { fy.powi(3) } else { l / K };
+    let z = if fz.powi(3) > E {
+        fz.powi(3)
+    } else {
+        (116.0 * fz - 16.0) / K
+    };
+    let [x, y, z] = [x * WHITE[0], y * WHITE[1], z * WHITE[2]];
+
+    // xyz d50 straight to linear srgb, bradford adaptation folded in
+    [
+        3.134_136 * x - 1.617_386_3 * y - 0.490_661_9 * z,
+        -0.978_795_5 * x + 1.916_254_6 * y + 0.033_442_7 * z,
+        0.071_955_4 * x - 0.228_976_8 * y + 1.405_386_1 * z,
+    ]
+}
+
+/// maps bytes to an srgb triple in 0.0..=1.0
+pub(crate) fn from_bytes((r, g, b): (u8, u8, u8)) -> [f32; 3] {
+    [r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0]
+}
+
+/// maps an srgb triple in 0.0..=1.0 to bytes, clipping out of gamut values
+pub(crate) fn to_bytes(rgb: [f32; 3]) -> (u8, u8, u8) {
+    let byte = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    (byte(rgb[0]), byte(rgb[1]), byte(rgb[2]))
+}
@@ -0,0 +1,552 @@
+use std::fmt::{self, Display};
+use std::io::{self, IsTerminal, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::color::Color;
+use crate::gradient::Gradient;
+use crate::style::Style;
+use crate::term::{self, ColorChoice};
+
+/// a named set of spinner frames and how fast they change
+///
+/// example:
+/// ```rs
+/// let frames = Frames::from_name("moon").unwrap_or(Frames::DOTS);
+/// for frames in Frames::all() {
+///     println!("{}: {}", frames.name(), frames.frames().join(" "));
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Frames {
+    name: &'static str,
+    frames: &'static [&'static str],
+    interval: u64,
+}
+
+macro_rules! frames {
+    ($($(#[$doc:meta])* $constant:ident $name:literal $interval:literal [$($frame:literal),+ $(,)?])+) => {
+        impl Frames {
+            $(
+                $(#[$doc])*
+                pub const $constant: Frames = Frames {
+                    name: $name,
+                    frames: &[$($frame),+],
+                    interval: $interval,
+                };
+            )+
+        }
+
+        const ALL: &[Frames] = &[$(Frames::$constant),+];
+    };
+}
+
+frames! {
+    /// braille dots running around, `⠋⠙⠹⠸`
+    DOTS "dots" 80 ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"]
+    /// a turning line, `-\|/`
+    LINE "line" 130 ["-", "\\", "|", "/"]
+    /// a quarter circle going round, `◜◠◝◞◡◟`
+    ARC "arc" 100 ["◜", "◠", "◝", "◞", "◡", "◟"]
+    /// a circle filled by halves, `◐◓◑◒`
+    CIRCLE "circle" 120 ["◐", "◓", "◑", "◒"]
+    /// a square filled by quarters, `◰◳◲◱`
+    SQUARE "square" 180 ["◰", "◳", "◲", "◱"]
+    /// a bar bouncing between brackets, `[=== ]`
+    BOUNCING_BAR "bouncing_bar" 80 [
+        "[    ]", "[=   ]", "[==  ]", "[=== ]", "[ ===]", "[  ==]", "[   =]",
+        "[    ]", "[   =]", "[  ==]", "[ ===]", "[====]", "[=== ]", "[==  ]",
+        "[=   ]",
+    ]
+    /// a dot bouncing up and down, `⠁⠂⠄⠂`
+    BOUNCE "bounce" 120 ["⠁", "⠂", "⠄", "⠂"]
+    /// a growing and shrinking block, `▁▃▅▇`
+    GROW "grow" 120 ["▁", "▃", "▄", "▅", "▆", "▇", "▆", "▅", "▄", "▃"]
+    /// an arrow going round, `←↖↑↗`
+    ARROW "arrow" 100 ["←", "↖", "↑", "↗", "→", "↘", "↓", "↙"]
+    /// clock faces, one per hour
+    CLOCK "clock" 100 [
+        "🕛", "🕐", "🕑", "🕒", "🕓", "🕔", "🕕", "🕖", "🕗", "🕘", "🕙", "🕚",
+    ]
+    /// moon phases, `🌑🌒🌓🌔🌕`
+    MOON "moon" 80 ["🌑", "🌒", "🌓", "🌔", "🌕", "🌖", "🌗", "🌘"]
+    /// two shapes taking turns, `⊶⊷`
+    TOGGLE "toggle" 250 ["⊶", "⊷"]
+    /// dots filling up, for terminals without unicode, `...`
+    SIMPLE_DOTS "simple_dots" 400 [".  ", ".. ", "...", "   "]
+}
+
+impl Frames {
+    /// looks a frame set up by name, ascii case-insensitive
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::all().find(|f| f.name.eq_ignore_ascii_case(name))
+    }
+
+    /// every frame set
+    pub fn all() -> impl Iterator<Item = Frames> {
+        ALL.iter().copied()
+    }
+
+    /// name of the set, like `dots`
+    pub fn name(self) -> &'static str {
+        self.name
+    }
+
+    /// the frames in order
+    pub fn frames(self) -> &'static [&'static str] {
+        self.frames
+    }
+
+    /// how long every frame is shown
+    pub fn interval(self) -> Duration {
+        Duration::from_millis(self.interval)
+    }
+}
+
+impl Default for Frames {
+    fn default() -> Self {
+        Frames::DOTS
+    }
+}
+
+/// an animated spinner in front of a message, for work of unknown length
+///
+/// a spinner is set up and then started, which draws it from a background
+/// thread until the returned [`SpinnerHandle`] finishes it. unlike
+/// `blink`, which many terminals ignore, the animation is drawn by the
+/// crate itself. when colors are off for the output, stderr unless changed
+/// and checked for a terminal, only the final line is printed
+///
+/// [`Spinner::frame`] renders any frame without a thread, for tests
+///
+/// example:
+/// ```rs
+/// let spinner = Spinner::new(Frames::ARC)
+///     .colors(Gradient::parse(&["#ff0080", "#7928ca", "#0070f3"])?)
+///     .message("resolving dependencies")
+///     .start();
+/// match resolve() {
+///     Ok(n) => spinner.success(format!("resolved {} packages", n)),
+///     Err(e) => spinner.failure(e),
+/// }
+/// ```
+pub struct Spinner {
+    frames: Vec<String>,
+    interval: Duration,
+    style: Style,
+    colors: Option<Gradient>,
+    cycle: usize,
+    message: String,
+    // finishing symbols, painted when they are shown
+    success: (String, Style),
+    failure: (String, Style),
+    out: Box<dyn Write + Send>,
+    terminal: bool,
+}
+
+impl Spinner {
+    /// a spinner drawn to stderr with the frames and speed of `frames`
+    pub fn new(frames: Frames) -> Self {
+        Self::custom(frames.frames(), frames.interval())
+    }
+
+    /// a spinner with frames of its own, each shown for `interval`
+    pub fn custom<I>(frames: I, interval: Duration) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        let frames: Vec<String> = frames.into_iter().map(|f| f.to_string()).collect();
+        Spinner {
+            cycle: frames.len().max(1),
+            frames,
+            interval,
+            style: Style::new(),
+            colors: None,
+            message: String::new(),
+            success: ("✔".to_string(), Style::new().fg(Color::Ansi16(2))),
+            failure: ("✖".to_string(), Style::new().fg(Color::Ansi16(1))),
+            out: Box::new(io::stderr()),
+            terminal: io::stderr().is_terminal(),
+        }
+    }
+
+    /// how long every frame is shown
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// the style of the frames, colors from [`Spinner::colors`] win over
+    /// its foreground
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// colors the frames one after another along `gradient`, going there
+    /// and back again once per round of frames
+    pub fn colors(mut self, gradient: Gradient) -> Self {
+        self.colors = Some(gradient);
+        self
+    }
+
+    /// frames it takes the colors to go along the gradient and back, one
+    /// round of frames by default
+    pub fn cycle(mut self, frames: usize) -> Self {
+        self.cycle = frames.max(1);
+        self
+    }
+
+    /// text shown after the spinner
+    pub fn message(mut self, message: impl Display) -> Self {
+        self.message = message.to_string();
+        self
+    }
+
+    /// shown in place of the spinner by [`SpinnerHandle::success`], a green
+    /// `✔` by default, staying green
+    pub fn success_symbol(mut self, symbol: impl Display) -> Self {
+        self.success.0 = symbol.to_string();
+        self
+    }
+
+    /// shown in place of the spinner by [`SpinnerHandle::failure`], a red
+    /// `✖` by default, staying red
+    pub fn failure_symbol(mut self, symbol: impl Display) -> Self {
+        self.failure.0 = symbol.to_string();
+        self
+    }
+
+    /// where the spinner is drawn, stderr by default, taken as not being a
+    /// terminal
+    pub fn output(mut self, out: impl Write + Send + 'static) -> Self {
+        self.out = Box::new(out);
+        self.terminal = false;
+        self
+    }
+
+    /// whether the output set with [`Spinner::output`] is a terminal,
+    /// which animates the spinner under `ColorChoice::Auto`
+    pub fn terminal(mut self, terminal: bool) -> Self {
+        self.terminal = terminal;
+        self
+    }
+
+    /// the line shown at `tick`, counting frames from 0 since the start
+    pub fn frame(&self, tick: usize) -> String {
+        let Some(frame) = self.frames.get(tick % self.frames.len().max(1)) else {
+            return self.message.clone();
+        };
+        let mut style = self.style;
+        if let Some(gradient) = &self.colors {
+            // there and back so the colors don't jump at the end of a round
+            let t = (tick % self.cycle) as f32 / self.cycle as f32;
+            style = style.fg(gradient.sample(1.0 - (2.0 * t - 1.0).abs()));
+        }
+        self.line(&style.paint(frame).to_string())
+    }
+
+    fn line(&self, symbol: &str) -> String {
+        match self.message.is_empty() {
+            true => symbol.to_string(),
+            false => format!("{} {}", symbol, self.message),
+        }
+    }
+
+    /// starts drawing the spinner on a background thread
+    pub fn start(self) -> SpinnerHandle {
+        // decided with the color choice of the thread starting it, for the
+        // output rather than the color stream, and drawn at its color level
+        let animate = term::colors_enabled_for(self.terminal);
+        let choice = match animate {
+            true => ColorChoice::Always,
+            false => ColorChoice::Never,
+        };
+        let level = term::color_level();
+        let (sender, receiver) = mpsc::channel();
+        let thread = thread::spawn(move || {
+            term::with_color_level(level, || {
+                term::with_color_choice(choice, || self.run(receiver, animate))
+            })
+        });
+        SpinnerHandle {
+            sender,
+            thread: Some(thread),
+        }
+    }
+
+    /// draws the frames until told to finish
+    fn run(mut self, receiver: Receiver<Command>, animate: bool) {
+        let mut ticker = Ticker::new(self.interval, Instant::now());
+        loop {
+            if animate {
+                let text = format!("\r{}\x1B[K", self.frame(ticker.tick));
+                self.write(&text);
+            }
+            match receiver.recv_timeout(ticker.wait(Instant::now())) {
+                // drawn again with the same frame
+                Ok(Command::Message(message)) => self.message = message,
+                Ok(Command::Finish(end)) => {
+                    self.finish(end, animate);
+                    break;
+                }
+                Err(RecvTimeoutError::Timeout) => ticker.advance(Instant::now()),
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.finish(End::Clear, animate);
+                    break;
+                }
+            }
+        }
+    }
+
+    fn finish(&mut self, end: End, animate: bool) {
+        if animate {
+            self.write("\r\x1B[K");
+        }
+        let ((symbol, style), message) = match end {
+            End::Clear => return,
+            End::Success(message) => (&self.success, message),
+            End::Failure(message) => (&self.failure, message),
+        };
+        let symbol = style.paint(symbol).to_string();
+        self.message = message;
+        let text = format!("{}\n", self.line(&symbol));
+        self.write(&text);
+    }
+
+    fn write(&mut self, text: &str) {
+        // the spinner is only for show, a broken output must not stop the work
+        let _ = self.out.write_all(text.as_bytes());
+        let _ = self.out.flush();
+    }
+}
+
+impl fmt::Debug for Spinner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Spinner")
+            .field("frames", &self.frames)
+            .field("interval", &self.interval)
+            .field("message", &self.message)
+            .finish_non_exhaustive()
+    }
+}
+
+enum Command {
+    Message(String),
+    Finish(End),
+}
+
+enum End {
+    Clear,
+    Success(String),
+    Failure(String),
+}
+
+/// a running [`Spinner`], dropping it stops and clears the spinner
+#[derive(Debug)]
+pub struct SpinnerHandle {
+    sender: Sender<Command>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl SpinnerHandle {
+    /// changes the text shown after the spinner
+    pub fn set_message(&self, message: impl Display) {
+        let _ = self.sender.send(Command::Message(message.to_string()));
+    }
+
+    /// replaces the spinner with the success symbol and `message`
+    pub fn success(self, message: impl Display) {
+        self.end(End::Success(message.to_string()));
+    }
+
+    /// replaces the spinner with the failure symbol and `message`
+    pub fn failure(self, message: impl Display) {
+        self.end(End::Failure(message.to_string()));
+    }
+
+    /// removes the spinner from the screen
+    pub fn stop(self) {
+        self.end(End::Clear);
+    }
+
+    fn end(mut self, end: End) {
+        let _ = self.sender.send(Command::Finish(end));
+        self.join();
+    }
+
+    fn join(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for SpinnerHandle {
+    fn drop(&mut self) {
+        let _ = self.sender.send(Command::Finish(End::Clear));
+        self.join();
+    }
+}
+
+/// which frame is shown and when the next one is due, kept apart from
+/// the messages so frames keep their pace however often those change
+struct Ticker {
+    tick: usize,
+    next: Instant,
+    interval: Duration,
+}
+
+impl Ticker {
+    fn new(interval: Duration, now: Instant) -> Self {
+        Ticker {
+            tick: 0,
+            next: now + interval,
+            interval,
+        }
+    }
+
+    /// how long until the next frame is due
+    fn wait(&self, now: Instant) -> Duration {
+        self.next.saturating_duration_since(now)
+    }
+
+    /// moves on to the next frame, due an interval after this one or right
+    /// away when running behind
+    fn advance(&mut self, now: Instant) {
+        self.tick += 1;
+        self.next = (self.next + self.interval).max(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::term::colored;
+    use std::sync::{Arc, Mutex};
+
+    /// an output the test can read back
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn frame_sets_are_named() {
+        assert_eq!(Frames::from_name("MOON"), Some(Frames::MOON));
+        assert_eq!(Frames::from_name("nope"), None);
+        for frames in Frames::all() {
+            assert!(!frames.frames().is_empty());
+            assert_eq!(Frames::from_name(frames.name()), Some(frames));
+        }
+    }
+
+    #[test]
+    fn frames_wrap_around() {
+        let plain = |spinner: &Spinner, tick| {
+            term::with_color_choice(ColorChoice::Never, || spinner.frame(tick))
+        };
+        let spinner = Spinner::custom(["a", "b", "c"], Duration::from_millis(80));
+        assert_eq!(plain(&spinner, 0), "a");
+        assert_eq!(plain(&spinner, 3), "a");
+        assert_eq!(plain(&spinner, 7), "b");
+        let spinner = spinner.message("loading");
+        assert_eq!(plain(&spinner, 5), "c loading");
+    }
+
+    #[test]
+    fn colors_go_there_and_back() {
+        let gradient = Gradient::even([Color::Rgb(255, 0, 0), Color::Rgb(0, 0, 255)]);
+        let spinner = Spinner::custom(["a", "b", "c"], Duration::from_millis(80))
+            .colors(gradient.clone())
+            .cycle(4);
+        let expected = |tick: usize, at: f32| {
+            let frame = ["a", "b", "c"][tick % 3];
+            Style::new()
+                .fg(gradient.sample(at))
+                .paint(frame)
+                .to_string()
+        };
+        colored(|| {
+            for (tick, at) in [(0, 0.0), (1, 0.5), (2, 1.0), (3, 0.5), (4, 0.0), (6, 1.0)] {
+                assert_eq!(spinner.frame(tick), expected(tick, at), "tick {}", tick);
+            }
+        });
+    }
+
+    #[test]
+    fn no_escapes_without_colors() {
+        let spinner = Spinner::new(Frames::LINE)
+            .style(Style::new().bold())
+            .colors(Gradient::even([
+                Color::Rgb(255, 0, 0),
+                Color::Rgb(0, 0, 255),
+            ]))
+            .message("working");
+        let frame = term::with_color_choice(ColorChoice::Never, || spinner.frame(1));
+        assert_eq!(frame, "\\ working");
+    }
+
+    #[test]
+    fn messages_dont_hold_the_frames_back() {
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+        let mut ticker = Ticker::new(ms(80), start);
+        // waking up for a message leaves the next frame where it was
+        assert_eq!(ticker.wait(start + ms(30)), ms(50));
+        assert_eq!(ticker.wait(start + ms(70)), ms(10));
+        ticker.advance(start + ms(80));
+        assert_eq!((ticker.tick, ticker.wait(start + ms(80))), (1, ms(80)));
+        // a late frame doesn't push back the ones after it
+        ticker.advance(start + ms(200));
+        assert_eq!((ticker.tick, ticker.wait(start + ms(200))), (2, ms(40)));
+        // running far behind draws the next one right away, then keeps pace
+        ticker.advance(start + ms(400));
+        assert_eq!((ticker.tick, ticker.wait(start + ms(400))), (3, ms(0)));
+        ticker.advance(start + ms(400));
+        assert_eq!((ticker.tick, ticker.wait(start + ms(400))), (4, ms(80)));
+    }
+
+    #[test]
+    fn messages_redraw_the_same_frame() {
+        let out = Shared::default();
+        let (sender, receiver) = mpsc::channel();
+        for message in ["one", "two"] {
+            sender.send(Command::Message(message.to_string())).unwrap();
+        }
+        sender.send(Command::Finish(End::Clear)).unwrap();
+        term::with_color_choice(ColorChoice::Never, || {
+            Spinner::custom(["a", "b"], Duration::from_secs(60))
+                .output(out.clone())
+                .run(receiver, true)
+        });
+        let drawn = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(drawn, "\ra\x1B[K\ra one\x1B[K\ra two\x1B[K\r\x1B[K");
+    }
+
+    #[test]
+    fn symbols_keep_their_colors() {
+        let spinner = Spinner::new(Frames::LINE)
+            .success_symbol("ok")
+            .failure_symbol("no");
+        assert_eq!(
+            spinner.success,
+            ("ok".to_string(), Style::new().fg(Color::Ansi16(2)))
+        );
+        assert_eq!(
+            spinner.failure,
+            ("no".to_string(), Style::new().fg(Color::Ansi16(1)))
+        );
+    }
+}
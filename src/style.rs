@@ -0,0 +1,1109 @@
+use std::error::Error;
+use std::fmt::{self, Display, Write};
+use std::ops::{BitAnd, BitOr, BitOrAssign, Sub};
+use std::str::FromStr;
+
+use crate::color::{Color, Layer, NamedColor};
+use crate::parse::ColorParseError;
+use crate::{ansi, term};
+
+/// a set of text attributes like bold or italic
+///
+/// attributes combine with `|` and are removed with `-`
+///
+/// example:
+/// ```rs
+/// let emphasis = Attributes::BOLD | Attributes::ITALIC;
+/// assert!(emphasis.contains(Attributes::BOLD));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Attributes(u16);
+
+// bit n is sgr code n
+const NAMES: [(&str, Attributes); 9] = [
+    ("bold", Attributes::BOLD),
+    ("dim", Attributes::DIM),
+    ("italic", Attributes::ITALIC),
+    ("underline", Attributes::UNDERLINE),
+    ("blink", Attributes::BLINK),
+    ("blink_fast", Attributes::BLINK_FAST),
+    ("reverse", Attributes::REVERSE),
+    ("hidden", Attributes::HIDDEN),
+    ("strikethrough", Attributes::STRIKETHROUGH),
+];
+
+impl Attributes {
+    pub const BOLD: Self = Attributes(1 << 1);
+    pub const DIM: Self = Attributes(1 << 2);
+    pub const ITALIC: Self = Attributes(1 << 3);
+    pub const UNDERLINE: Self = Attributes(1 << 4);
+    pub const BLINK: Self = Attributes(1 << 5);
+    pub const BLINK_FAST: Self = Attributes(1 << 6);
+    pub const REVERSE: Self = Attributes(1 << 7);
+    pub const HIDDEN: Self = Attributes(1 << 8);
+    pub const STRIKETHROUGH: Self = Attributes(1 << 9);
+
+    /// no attributes
+    pub const fn empty() -> Self {
+        Attributes(0)
+    }
+
+    /// whether no attribute is set
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// whether every attribute in `other` is set
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// attributes set in either
+    pub const fn union(self, other: Self) -> Self {
+        Attributes(self.0 | other.0)
+    }
+
+    /// attributes set in both
+    pub const fn intersection(self, other: Self) -> Self {
+        Attributes(self.0 & other.0)
+    }
+
+    /// attributes set in `self` but not in `other`
+    pub const fn difference(self, other: Self) -> Self {
+        Attributes(self.0 & !other.0)
+    }
+
+    /// sets every attribute in `other`
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    /// clears every attribute in `other`
+    pub fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+
+    /// the attribute sgr code `code` (1 to 9) turns on
+    pub(crate) const fn from_code(code: u16) -> Self {
+        Attributes(1 << code)
+    }
+
+    /// sgr codes that turn the attributes on, in ascending order
+    fn codes(self) -> impl Iterator<Item = u8> {
+        (1..16).filter(move |code| self.0 & (1 << code) != 0)
+    }
+}
+
+impl fmt::Debug for Attributes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("Attributes(empty)");
+        }
+        f.write_str("Attributes(")?;
+        let mut first = true;
+        for (name, attribute) in NAMES {
+            if self.contains(attribute) {
+                if !first {
+                    f.write_str(" | ")?;
+                }
+                f.write_str(name)?;
+                first = false;
+            }
+        }
+        f.write_str(")")
+    }
+}
+
+impl BitOr for Attributes {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        self.union(other)
+    }
+}
+
+impl BitOrAssign for Attributes {
+    fn bitor_assign(&mut self, other: Self) {
+        self.insert(other);
+    }
+}
+
+impl BitAnd for Attributes {
+    type Output = Self;
+
+    fn bitand(self, other: Self) -> Self {
+        self.intersection(other)
+    }
+}
+
+impl Sub for Attributes {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self.difference(other)
+    }
+}
+
+/// colors and text attributes, describing how text looks
+///
+/// `None` colors leave whatever color is already in effect alone. styles
+/// don't allocate, they only turn into escape sequences when displayed
+/// through [`Styled`] or [`Style::transition`]
+///
+/// styles round-trip through strings, which makes them easy to keep in a
+/// config file: attribute names, then the text color, then the background
+/// after `on` and the underline color after `under`
+///
+/// example:
+/// ```rs
+/// let warning = Style::new().bold().fg(Color::Ansi16(3));
+/// println!("{}", warning.paint(&"careful!"));
+///
+/// let error: Style = "bold underline red on black under #ff8800".parse()?;
+/// assert_eq!(error.to_string(), "bold underline red on black under #ff8800");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Style {
+    /// text color
+    pub fg: Option<Color>,
+    /// background color
+    pub bg: Option<Color>,
+    /// color of underlines, on terminals that support it
+    pub underline_color: Option<Color>,
+    /// text attributes
+    pub attributes: Attributes,
+}
+
+impl Style {
+    /// a style that changes nothing
+    pub const fn new() -> Self {
+        Style {
+            fg: None,
+            bg: None,
+            underline_color: None,
+            attributes: Attributes::empty(),
+        }
+    }
+
+    /// sets the text color
+    pub const fn fg(mut self, color: Color) -> Self {
+        self.fg = Some(color);
+        self
+    }
+
+    /// sets the background color
+    pub const fn bg(mut self, color: Color) -> Self {
+        self.bg = Some(color);
+        self
+    }
+
+    /// sets the underline color
+    pub const fn underline_color(mut self, color: Color) -> Self {
+        self.underline_color = Some(color);
+        self
+    }
+
+    /// adds `attributes`
+    pub const fn with(mut self, attributes: Attributes) -> Self {
+        self.attributes = self.attributes.union(attributes);
+        self
+    }
+
+    /// makes text bold
+    pub const fn bold(self) -> Self {
+        self.with(Attributes::BOLD)
+    }
+
+    /// makes text appear dimmer
+    pub const fn dim(self) -> Self {
+        self.with(Attributes::DIM)
+    }
+
+    /// makes text italic
+    pub const fn italic(self) -> Self {
+        self.with(Attributes::ITALIC)
+    }
+
+    /// adds underline to text
+    pub const fn underline(self) -> Self {
+        self.with(Attributes::UNDERLINE)
+    }
+
+    /// makes text blink
+    pub const fn blink(self) -> Self {
+        self.with(Attributes::BLINK)
+    }
+
+    /// makes text blink rapidly
+    pub const fn blink_fast(self) -> Self {
+        self.with(Attributes::BLINK_FAST)
+    }
+
+    /// swaps foreground and background colors
+    pub const fn reverse(self) -> Self {
+        self.with(Attributes::REVERSE)
+    }
+
+    /// hides text while keeping its space
+    pub const fn hidden(self) -> Self {
+        self.with(Attributes::HIDDEN)
+    }
+
+    /// adds strikethrough to text
+    pub const fn strikethrough(self) -> Self {
+        self.with(Attributes::STRIKETHROUGH)
+    }
+
+    /// whether the style sets neither colors nor attributes
+    pub const fn is_plain(&self) -> bool {
+        self.fg.is_none()
+            && self.bg.is_none()
+            && self.underline_color.is_none()
+            && self.attributes.is_empty()
+    }
+
+    /// layers `other` on top of this style, colors set in `other` win and
+    /// attributes from both are kept
+    ///
+    /// example:
+    /// ```rs
+    /// let base = Style::new().fg(Color::Ansi16(7)).bg(Color::Ansi16(0));
+    /// let error = base.merge(Style::new().bold().fg(Color::Ansi16(1)));
+    /// ```
+    pub fn merge(self, other: Style) -> Self {
+        Style {
+            fg: other.fg.or(self.fg),
+            bg: other.bg.or(self.bg),
+            underline_color: other.underline_color.or(self.underline_color),
+            attributes: self.attributes | other.attributes,
+        }
+    }
+
+    /// wraps `value` so it's displayed with this style
+    pub fn paint<T: Display + ?Sized>(self, value: &T) -> Styled<'_, T> {
+        Styled { value, style: self }
+    }
+
+    /// the shortest escape sequence that switches from this style to
+    /// `next`, empty when they're the same
+    ///
+    /// only what differs is changed, unless a full reset turns out shorter
+    ///
+    /// example:
+    /// ```rs
+    /// let a = Style::new().bold().fg(Color::Ansi16(1));
+    /// let b = Style::new().bold().fg(Color::Ansi16(4));
+    /// assert_eq!(a.transition(b).to_string(), "\x1B[34m");
+    /// ```
+    pub const fn transition(self, next: Style) -> Transition {
+        Transition {
+            from: self,
+            to: next,
+        }
+    }
+
+    /// the style a single sgr code stands for, if it maps onto one
+    pub(crate) fn from_code(code: usize) -> Option<Self> {
+        let style = Style::new();
+        Some(match code {
+            1..=9 => style.with(Attributes::from_code(code as u16)),
+            30..=37 => style.fg(Color::Ansi16(code as u8 - 30)),
+            40..=47 => style.bg(Color::Ansi16(code as u8 - 40)),
+            90..=97 => style.fg(Color::Ansi16(code as u8 - 82)),
+            100..=107 => style.bg(Color::Ansi16(code as u8 - 92)),
+            _ => return None,
+        })
+    }
+
+    /// writes the parameters that turn this style on from a plain state
+    fn write_on<W: Write>(&self, params: &mut Params<W>) -> fmt::Result {
+        for code in self.attributes.codes() {
+            params.code(code)?;
+        }
+        params.color(self.fg, Layer::Fg)?;
+        params.color(self.bg, Layer::Bg)?;
+        params.color(self.underline_color, Layer::Underline)
+    }
+
+    /// writes the parameters that change only what differs from `self`
+    fn write_changes<W: Write>(&self, next: &Style, params: &mut Params<W>) -> fmt::Result {
+        let removed = self.attributes - next.attributes;
+        let mut added = next.attributes - self.attributes;
+
+        // some codes turn off two attributes at once, switch back on the
+        // one that should stay
+        let pairs = [
+            (Attributes::BOLD | Attributes::DIM, 22),
+            (Attributes::ITALIC, 23),
+            (Attributes::UNDERLINE, 24),
+            (Attributes::BLINK | Attributes::BLINK_FAST, 25),
+            (Attributes::REVERSE, 27),
+            (Attributes::HIDDEN, 28),
+            (Attributes::STRIKETHROUGH, 29),
+        ];
+        for (group, off) in pairs {
+            if !(removed & group).is_empty() {
+                params.code(off)?;
+                added |= next.attributes & group;
+            }
+        }
+        for code in added.codes() {
+            params.code(code)?;
+        }
+
+        let colors = [
+            (self.fg, next.fg, Layer::Fg, 39),
+            (self.bg, next.bg, Layer::Bg, 49),
+            (
+                self.underline_color,
+                next.underline_color,
+                Layer::Underline,
+                59,
+            ),
+        ];
+        for (from, to, layer, default) in colors {
+            if from != to {
+                match to {
+                    Some(_) => params.color(to, layer)?,
+                    None => params.code(default)?,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// the sequence that turns this style on from a plain state, empty for
+    /// a plain style
+    pub(crate) fn prefix(&self) -> String {
+        let mut prefix = String::new();
+        if !self.is_plain() {
+            let _ = self.write_prefix(&mut prefix);
+        }
+        prefix
+    }
+
+    /// writes the sequence that turns this style on from a plain state
+    fn write_prefix(&self, out: &mut impl Write) -> fmt::Result {
+        let mut params = Params::new(out);
+        self.write_on(&mut params)?;
+        params.finish()
+    }
+}
+
+impl Display for Style {
+    /// writes the style in the form [`Style::from_str`] reads
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut words = Words { f, first: true };
+        for (name, attribute) in NAMES {
+            if self.attributes.contains(attribute) {
+                words.word(format_args!("{}", name))?;
+            }
+        }
+        if let Some(fg) = self.fg {
+            words.word(format_args!("{}", fg))?;
+        }
+        if let Some(bg) = self.bg {
+            words.word(format_args!("on {}", bg))?;
+        }
+        if let Some(color) = self.underline_color {
+            words.word(format_args!("under {}", color))?;
+        }
+        Ok(())
+    }
+}
+
+struct Words<'a, 'b> {
+    f: &'a mut fmt::Formatter<'b>,
+    first: bool,
+}
+
+impl Words<'_, '_> {
+    fn word(&mut self, word: fmt::Arguments<'_>) -> fmt::Result {
+        if !self.first {
+            self.f.write_char(' ')?;
+        }
+        self.first = false;
+        self.f.write_fmt(word)
+    }
+}
+
+/// error returned when a string can't be parsed into a [`Style`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StyleParseError {
+    /// a word wasn't an attribute or a valid color, offsets point into the
+    /// whole style string
+    Color(ColorParseError),
+    /// `on` or `under` wasn't followed by a color
+    MissingColor { offset: usize },
+    /// more than one text color was given
+    DuplicateColor { offset: usize },
+}
+
+impl StyleParseError {
+    /// byte offset of the error in the parsed string, if it has one
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            StyleParseError::Color(error) => error.offset(),
+            StyleParseError::MissingColor { offset }
+            | StyleParseError::DuplicateColor { offset } => Some(*offset),
+        }
+    }
+}
+
+impl fmt::Display for StyleParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StyleParseError::Color(error) => error.fmt(f),
+            StyleParseError::MissingColor { offset } => {
+                write!(f, "expected a color after byte {}", offset)
+            }
+            StyleParseError::DuplicateColor { offset } => {
+                write!(f, "second text color at byte {}", offset)
+            }
+        }
+    }
+}
+
+impl Error for StyleParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StyleParseError::Color(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Style {
+    type Err = StyleParseError;
+
+    /// parses words like `bold italic red on #1e293b under blue_500`,
+    /// colors take any syntax [`Color`] parses
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut style = Style::new();
+        let mut words = split_words(input);
+        while let Some((offset, word)) = words.next() {
+            if let Some((_, attribute)) = NAMES
+                .iter()
+                .find(|(name, _)| word.eq_ignore_ascii_case(name))
+            {
+                style.attributes |= *attribute;
+                continue;
+            }
+
+            let slot = if word.eq_ignore_ascii_case("on") {
+                &mut style.bg
+            } else if word.eq_ignore_ascii_case("under") {
+                &mut style.underline_color
+            } else {
+                if style.fg.is_some() {
+                    return Err(StyleParseError::DuplicateColor { offset });
+                }
+                style.fg = Some(parse_color(offset, word)?);
+                continue;
+            };
+            let (offset, word) = words.next().ok_or(StyleParseError::MissingColor {
+                offset: offset + word.len(),
+            })?;
+            *slot = Some(parse_color(offset, word)?);
+        }
+        Ok(style)
+    }
+}
+
+fn parse_color(offset: usize, word: &str) -> Result<Color, StyleParseError> {
+    word.parse()
+        .map_err(|e: ColorParseError| StyleParseError::Color(e.shifted(offset)))
+}
+
+/// splits on whitespace outside of parentheses, so `rgb(1 2 3)` stays one
+/// word, yielding each word with its byte offset
+fn split_words(s: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut rest = 0;
+    std::iter::from_fn(move || {
+        let start = rest + s[rest..].find(|c: char| !c.is_whitespace())?;
+        let mut depth = 0usize;
+        let mut end = s.len();
+        for (i, c) in s[start..].char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth = depth.saturating_sub(1),
+                c if c.is_whitespace() && depth == 0 => {
+                    end = start + i;
+                    break;
+                }
+                _ => {}
+            }
+        }
+        rest = end;
+        Some((start, &s[start..end]))
+    })
+}
+
+/// the escape sequence between two styles, see [`Style::transition`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition {
+    from: Style,
+    to: Style,
+}
+
+impl Display for Transition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.from == self.to || !term::colors_enabled() {
+            return Ok(());
+        }
+        if self.to.is_plain() {
+            return f.write_str("\x1B[0m");
+        }
+
+        let changes = |out: &mut dyn Write| {
+            let mut params = Params::new(out);
+            self.from.write_changes(&self.to, &mut params)?;
+            params.finish()
+        };
+        let reset = |out: &mut dyn Write| {
+            let mut params = Params::new(out);
+            params.code(0)?;
+            self.to.write_on(&mut params)?;
+            params.finish()
+        };
+
+        let mut changes_len = Count(0);
+        let mut reset_len = Count(0);
+        changes(&mut changes_len)?;
+        reset(&mut reset_len)?;
+        if changes_len.0 <= reset_len.0 {
+            changes(f)
+        } else {
+            reset(f)
+        }
+    }
+}
+
+/// counts the bytes written to it
+struct Count(usize);
+
+impl Write for Count {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
+}
+
+/// writes `;` separated sgr parameters, opening the sequence on the first
+/// one and writing nothing if there are none
+struct Params<W> {
+    out: W,
+    open: bool,
+}
+
+impl<W: Write> Params<W> {
+    fn new(out: W) -> Self {
+        Params { out, open: false }
+    }
+
+    fn separate(&mut self) -> fmt::Result {
+        if self.open {
+            self.out.write_char(';')
+        } else {
+            self.open = true;
+            self.out.write_str("\x1B[")
+        }
+    }
+
+    fn code(&mut self, code: u8) -> fmt::Result {
+        self.separate()?;
+        write!(self.out, "{}", code)
+    }
+
+    fn color(&mut self, color: Option<Color>, layer: Layer) -> fmt::Result {
+        match color {
+            Some(color) => {
+                self.separate()?;
+                color.write_params(layer, &mut self.out)
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn finish(mut self) -> fmt::Result {
+        if self.open {
+            self.out.write_char('m')?;
+        }
+        Ok(())
+    }
+}
+
+/// a value together with the [`Style`] it's displayed in
+///
+/// unlike the `String` returning methods on [`Colors`](crate::Colors),
+/// chaining on a `Styled` doesn't allocate, escapes are written straight to
+/// the formatter when the value is displayed. resets inside the value (from
+/// text that was already styled) switch the outer style back on afterwards
+///
+/// example:
+/// ```rs
+/// println!("{}", "hi!".styled().bold().text_red().bg_black());
+/// log::info!("{}", user.styled().underline().text_blue_500());
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Styled<'a, T: ?Sized> {
+    value: &'a T,
+    style: Style,
+}
+
+impl<'a, T: ?Sized> Styled<'a, T> {
+    /// wraps `value` without any styling
+    pub fn new(value: &'a T) -> Self {
+        Styled {
+            value,
+            style: Style::new(),
+        }
+    }
+
+    /// the style collected so far
+    pub fn style(&self) -> Style {
+        self.style
+    }
+
+    /// the wrapped value
+    pub fn value(&self) -> &'a T {
+        self.value
+    }
+
+    /// layers `style` on top of the current one, see [`Style::merge`]
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.style = self.style.merge(style);
+        self
+    }
+
+    /// sets text color to any [`Color`]
+    pub fn text_color(mut self, color: Color) -> Self {
+        self.style = self.style.fg(color);
+        self
+    }
+
+    /// sets background color to any [`Color`]
+    pub fn bg_color(mut self, color: Color) -> Self {
+        self.style = self.style.bg(color);
+        self
+    }
+
+    /// sets text color to the terminal's default, even inside a colored span
+    pub fn text_default(mut self) -> Self {
+        self.style = self.style.fg(Color::Default);
+        self
+    }
+
+    /// sets background color to the terminal's default, even inside a
+    /// colored span
+    pub fn bg_default(mut self) -> Self {
+        self.style = self.style.bg(Color::Default);
+        self
+    }
+
+    /// sets the color of underlines
+    pub fn underline_color(mut self, color: Color) -> Self {
+        self.style = self.style.underline_color(color);
+        self
+    }
+
+    /// makes text bold
+    pub fn bold(mut self) -> Self {
+        self.style = self.style.bold();
+        self
+    }
+
+    /// makes text appear dimmer
+    pub fn dim(mut self) -> Self {
+        self.style = self.style.dim();
+        self
+    }
+
+    /// makes text italic
+    pub fn italic(mut self) -> Self {
+        self.style = self.style.italic();
+        self
+    }
+
+    /// adds underline to text
+    pub fn underline(mut self) -> Self {
+        self.style = self.style.underline();
+        self
+    }
+
+    /// makes text blink
+    pub fn blink(mut self) -> Self {
+        self.style = self.style.blink();
+        self
+    }
+
+    /// makes text blink rapidly
+    pub fn blink_fast(mut self) -> Self {
+        self.style = self.style.blink_fast();
+        self
+    }
+
+    /// makes text blink slowly
+    pub fn blink_slow(self) -> Self {
+        self.blink()
+    }
+
+    /// swaps foreground and background colors
+    pub fn reverse(mut self) -> Self {
+        self.style = self.style.reverse();
+        self
+    }
+
+    /// hides text while keeping its space
+    pub fn hidden(mut self) -> Self {
+        self.style = self.style.hidden();
+        self
+    }
+
+    /// adds strikethrough to text
+    pub fn strikethrough(mut self) -> Self {
+        self.style = self.style.strikethrough();
+        self
+    }
+}
+
+impl<T: Display + ?Sized> Display for Styled<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.style.is_plain() || !term::colors_enabled() {
+            return write!(f, "{}", self.value);
+        }
+        write_nested(f, self.value, Outer::Style(&self.style))
+    }
+}
+
+/// the style wrapped around nested text, either a [`Style`] or raw sgr
+/// parameters from `Colors::code`
+#[derive(Clone, Copy)]
+pub(crate) enum Outer<'a> {
+    Style(&'a Style),
+    Params(&'a str),
+}
+
+impl Outer<'_> {
+    fn write_prefix(self, out: &mut impl Write) -> fmt::Result {
+        match self {
+            Outer::Style(style) => style.write_prefix(out),
+            Outer::Params(params) => write!(out, "\x1B[{}m", params),
+        }
+    }
+
+    /// writes the parameters, each one preceded by a `;`
+    fn write_params(self, out: &mut impl Write) -> fmt::Result {
+        match self {
+            Outer::Style(style) => style.write_on(&mut Params { out, open: true }),
+            Outer::Params(params) => write!(out, ";{}", params),
+        }
+    }
+}
+
+/// writes `value` in `outer`, switching `outer` back on after every reset
+/// inside `value` so inner styles only override what they set
+pub(crate) fn write_nested<W, T>(out: W, value: &T, outer: Outer<'_>) -> fmt::Result
+where
+    W: Write,
+    T: Display + ?Sized,
+{
+    let mut nested = Nested {
+        out,
+        outer,
+        scan: Scan::Text,
+        csi: [0; CSI_MAX],
+        csi_len: 0,
+        pending: false,
+    };
+    outer.write_prefix(&mut nested.out)?;
+    write!(nested, "{}", value)?;
+    nested.finish()
+}
+
+/// longest csi sequence [`Nested`] looks into, longer ones pass through
+const CSI_MAX: usize = 64;
+
+/// where [`Nested`] is inside an escape sequence, tracked across writes
+/// since a sequence may be split over several `write_str` calls
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Scan {
+    Text,
+    Escape,
+    // buffered in `csi` until its final byte
+    Csi,
+    // too long to buffer, passed through up to its final byte
+    LongCsi,
+    // osc, dcs and friends, up to bel or st
+    String,
+}
+
+struct Nested<'a, W> {
+    out: W,
+    outer: Outer<'a>,
+    scan: Scan,
+    csi: [u8; CSI_MAX],
+    csi_len: usize,
+    // set after a reset, `outer` is switched back on before what follows
+    pending: bool,
+}
+
+impl<W: Write> Nested<'_, W> {
+    fn resume(&mut self) -> fmt::Result {
+        if self.pending {
+            self.pending = false;
+            self.outer.write_prefix(&mut self.out)?;
+        }
+        Ok(())
+    }
+
+    /// handles a complete csi sequence sitting in `csi`
+    fn flush_csi(&mut self) -> fmt::Result {
+        let (buf, len) = (self.csi, std::mem::take(&mut self.csi_len));
+        // only ascii bytes are buffered
+        let csi = std::str::from_utf8(&buf[..len]).unwrap_or_default();
+        let params = match csi.strip_prefix("\x1B[").and_then(|s| s.strip_suffix('m')) {
+            Some(params) => params,
+            None => {
+                self.resume()?;
+                return self.out.write_str(csi);
+            }
+        };
+
+        match ansi::last_reset(params) {
+            // ends on a reset, wait for more text before switching back
+            Some(end) if end == params.len() => {
+                self.pending = true;
+                self.out.write_str(csi)
+            }
+            // reset followed by more parameters, slip `outer` in between
+            Some(end) => {
+                self.pending = false;
+                write!(self.out, "\x1B[{}", &params[..end])?;
+                self.outer.write_params(&mut self.out)?;
+                write!(self.out, "{}m", &params[end..])
+            }
+            None => {
+                self.resume()?;
+                self.out.write_str(csi)
+            }
+        }
+    }
+
+    fn finish(mut self) -> fmt::Result {
+        if self.scan == Scan::Escape {
+            self.out.write_char('\x1B')?;
+        }
+        if self.scan == Scan::Csi {
+            // unterminated, pass it through as is
+            let len = self.csi_len;
+            let csi = std::str::from_utf8(&self.csi[..len]).unwrap_or_default();
+            self.out.write_str(csi)?;
+        }
+        if self.pending {
+            // the value ended on its own reset
+            return Ok(());
+        }
+        self.out.write_str("\x1B[0m")
+    }
+}
+
+impl<W: Write> Write for Nested<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut start = 0;
+        for (i, c) in s.char_indices() {
+            self.scan = match (self.scan, c) {
+                (Scan::Text | Scan::String, '\x1B') => {
+                    self.out.write_str(&s[start..i])?;
+                    start = i;
+                    Scan::Escape
+                }
+                (Scan::Text, _) => {
+                    if self.pending {
+                        self.out.write_str(&s[start..i])?;
+                        self.resume()?;
+                        start = i;
+                    }
+                    Scan::Text
+                }
+                (Scan::Escape, '[') => {
+                    // the esc may have come in an earlier write
+                    start = i + 1;
+                    self.csi[..2].copy_from_slice(b"\x1B[");
+                    self.csi_len = 2;
+                    Scan::Csi
+                }
+                (Scan::Escape, _) => {
+                    self.resume()?;
+                    if start == i {
+                        self.out.write_char('\x1B')?;
+                    }
+                    if matches!(c, ']' | 'P' | 'X' | '^' | '_') {
+                        Scan::String
+                    } else {
+                        Scan::Text
+                    }
+                }
+                (Scan::Csi, _) => {
+                    start = i + c.len_utf8();
+                    if !c.is_ascii() || self.csi_len == CSI_MAX {
+                        let (buf, len) = (self.csi, self.csi_len);
+                        let csi = std::str::from_utf8(&buf[..len]).unwrap_or_default();
+                        self.resume()?;
+                        self.out.write_str(csi)?;
+                        self.out.write_char(c)?;
+                        self.csi_len = 0;
+                        if ('\x40'..='\x7E').contains(&c) {
+                            Scan::Text
+                        } else {
+                            Scan::LongCsi
+                        }
+                    } else {
+                        self.csi[self.csi_len] = c as u8;
+                        self.csi_len += 1;
+                        if ('\x40'..='\x7E').contains(&c) {
+                            self.flush_csi()?;
+                            Scan::Text
+                        } else {
+                            Scan::Csi
+                        }
+                    }
+                }
+                (Scan::LongCsi, '\x40'..='\x7E') => Scan::Text,
+                (Scan::LongCsi, _) => Scan::LongCsi,
+                (Scan::String, '\x07') => Scan::Text,
+                (Scan::String, _) => Scan::String,
+            };
+        }
+        if !matches!(self.scan, Scan::Escape | Scan::Csi) {
+            self.out.write_str(&s[start..])?;
+        }
+        Ok(())
+    }
+}
+
+macro_rules! palette {
+    ($($text:ident, $bg:ident => $color:expr;)*) => {
+        impl<T: ?Sized> Styled<'_, T> {
+            $(
+                #[doc = concat!("same as [`Colors::", stringify!($text), "`](crate::Colors::", stringify!($text), "), without allocating")]
+                pub fn $text(self) -> Self {
+                    self.text_color($color)
+                }
+
+                #[doc = concat!("same as [`Colors::", stringify!($bg), "`](crate::Colors::", stringify!($bg), "), without allocating")]
+                pub fn $bg(self) -> Self {
+                    self.bg_color($color)
+                }
+            )*
+        }
+    };
+}
+
+// the accents are taken from the named color table by method name
+macro_rules! accents {
+    ($($text:ident, $bg:ident;)*) => {
+        palette! {
+            $($text, $bg => Color::Named(const { NamedColor::accent(stringify!($text)) });)*
+        }
+    };
+}
+
+palette! {
+text_black, bg_black => Color::Ansi16(0);
+text_red, bg_red => Color::Ansi16(1);
+text_blue, bg_blue => Color::Ansi16(4);
+text_green, bg_green => Color::Ansi16(2);
+text_yellow, bg_yellow => Color::Ansi16(3);
+text_magenta, bg_magenta => Color::Ansi16(5);
+text_cyan, bg_cyan => Color::Ansi16(6);
+text_white, bg_white => Color::Ansi16(7);
+text_gray, bg_gray => Color::Ansi16(8);
+text_red_bright, bg_red_bright => Color::Ansi16(9);
+text_green_bright, bg_green_bright => Color::Ansi16(10);
+text_yellow_bright, bg_yellow_bright => Color::Ansi16(11);
+text_blue_bright, bg_blue_bright => Color::Ansi16(12);
+text_magenta_bright, bg_magenta_bright => Color::Ansi16(13);
+text_cyan_bright, bg_cyan_bright => Color::Ansi16(14);
+text_white_bright, bg_white_bright => Color::Ansi16(15);
+}
+
+accents! {
+text_red_50, bg_red_50;
+text_red_100, bg_red_100;
+text_red_200, bg_red_200;
+text_red_300, bg_red_300;
+text_red_400, bg_red_400;
+text_red_500, bg_red_500;
+text_red_600, bg_red_600;
+text_red_700, bg_red_700;
+text_red_800, bg_red_800;
+text_red_900, bg_red_900;
+text_red_950, bg_red_950;
+text_yellow_50, bg_yellow_50;
+text_yellow_100, bg_yellow_100;
+text_yellow_200, bg_yellow_200;
+text_yellow_300, bg_yellow_300;
+text_yellow_400, bg_yellow_400;
+text_yellow_500, bg_yellow_500;
+text_yellow_600, bg_yellow_600;
+text_yellow_700, bg_yellow_700;
+text_yellow_800, bg_yellow_800;
+text_yellow_900, bg_yellow_900;
+text_yellow_950, bg_yellow_950;
+text_green_50, bg_green_50;
+text_green_100, bg_green_100;
+text_green_200, bg_green_200;
+text_green_300, bg_green_300;
+text_green_400, bg_green_400;
+text_green_500, bg_green_500;
+text_green_600, bg_green_600;
+text_green_700, bg_green_700;
+text_green_800, bg_green_800;
+text_green_900, bg_green_900;
+text_green_950, bg_green_950;
+text_blue_50, bg_blue_50;
+text_blue_100, bg_blue_100;
+text_blue_200, bg_blue_200;
+text_blue_300, bg_blue_300;
+text_blue_400, bg_blue_400;
+text_blue_500, bg_blue_500;
+text_blue_600, bg_blue_600;
+text_blue_700, bg_blue_700;
+text_blue_800, bg_blue_800;
+text_blue_900, bg_blue_900;
+text_blue_950, bg_blue_950;
+text_purple_50, bg_purple_50;
+text_purple_100, bg_purple_100;
+text_purple_200, bg_purple_200;
+text_purple_300, bg_purple_300;
+text_purple_400, bg_purple_400;
+text_purple_500, bg_purple_500;
+text_purple_600, bg_purple_600;
+text_purple_700, bg_purple_700;
+text_purple_800, bg_purple_800;
+text_purple_900, bg_purple_900;
+text_purple_950, bg_purple_950;
+text_pink_50, bg_pink_50;
+text_pink_100, bg_pink_100;
+text_pink_200, bg_pink_200;
+text_pink_300, bg_pink_300;
+text_pink_400, bg_pink_400;
+text_pink_500, bg_pink_500;
+text_pink_600, bg_pink_600;
+text_pink_700, bg_pink_700;
+text_pink_800, bg_pink_800;
+text_pink_900, bg_pink_900;
+text_pink_950, bg_pink_950;
+text_black_50, bg_black_50;
+text_black_100, bg_black_100;
+text_black_200, bg_black_200;
+text_black_300, bg_black_300;
+text_black_400, bg_black_400;
+text_black_500, bg_black_500;
+text_black_600, bg_black_600;
+text_black_700, bg_black_700;
+text_black_800, bg_black_800;
+text_black_900, bg_black_900;
+text_black_950, bg_black_950;
+}
@@ -0,0 +1,496 @@
+use std::fmt::{self, Display};
+
+use crate::ansi;
+use crate::color::Color;
+use crate::panel::{Align, Border, BorderChars};
+use crate::style::Style;
+use crate::truncate::{self, TruncateOptions};
+use crate::width::display_width;
+use crate::wrap::{self, WrapOptions};
+
+/// what happens to cell text wider than its column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Overflow {
+    /// breaks the text into more lines, making the row taller
+    #[default]
+    Wrap,
+    /// cuts every line with `…`
+    Truncate,
+}
+
+/// one column of a [`Table`], its header, alignment and width limits
+///
+/// headers given as strings convert into columns with the defaults
+///
+/// example:
+/// ```rs
+/// let name = Column::new("name").min_width(8).flexible();
+/// let size = Column::new("size").align(Align::Right).max_width(10);
+/// let path = Column::new("path").max_width(30).overflow(Overflow::Truncate);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    header: String,
+    align: Align,
+    min: usize,
+    max: Option<usize>,
+    flexible: bool,
+    overflow: Overflow,
+}
+
+impl Column {
+    /// a left aligned column as wide as its widest cell
+    pub fn new(header: impl Display) -> Self {
+        Column {
+            header: header.to_string(),
+            align: Align::default(),
+            min: 0,
+            max: None,
+            flexible: false,
+            overflow: Overflow::default(),
+        }
+    }
+
+    /// where the header and the cells sit within the column
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// never narrower than `width` columns, not even to fit the table width
+    pub fn min_width(mut self, width: usize) -> Self {
+        self.min = width;
+        self
+    }
+
+    /// never wider than `width` columns, longer cells overflow
+    pub fn max_width(mut self, width: usize) -> Self {
+        self.max = Some(width);
+        self
+    }
+
+    /// exactly `width` columns
+    pub fn width(self, width: usize) -> Self {
+        self.min_width(width).max_width(width)
+    }
+
+    /// takes a share of the room left over when the table has a width
+    pub fn flexible(mut self) -> Self {
+        self.flexible = true;
+        self
+    }
+
+    /// what happens to cells wider than the column
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    fn clamp(&self, width: usize) -> usize {
+        let width = width.max(self.min);
+        self.max.map_or(width, |max| width.min(max.max(self.min)))
+    }
+
+    /// the lines of `cell` laid out in `width` columns
+    fn lines(&self, cell: &str, width: usize) -> Vec<String> {
+        match self.overflow {
+            Overflow::Wrap => wrap::wrap(cell, &WrapOptions::new(width)),
+            Overflow::Truncate => {
+                let options = TruncateOptions::new(width);
+                ansi::lines(cell)
+                    .iter()
+                    .map(|line| truncate::truncate(line, &options))
+                    .collect()
+            }
+        }
+    }
+}
+
+impl From<&str> for Column {
+    fn from(header: &str) -> Self {
+        Column::new(header)
+    }
+}
+
+impl From<String> for Column {
+    fn from(header: String) -> Self {
+        Column::new(header)
+    }
+}
+
+/// a table with a header row, drawn with the same borders as a [`Panel`]
+///
+/// columns are measured in terminal columns, so cells styled with this
+/// crate, wide characters and emoji line up. rows may have fewer cells than
+/// there are columns, the rest are left empty
+///
+/// example:
+/// ```rs
+/// let table = Table::new()
+///     .header(["name", "status"])
+///     .column(Column::new("time").align(Align::Right))
+///     .row(["build".to_string(), "ok".text_green(), "1.2s".to_string()])
+///     .row(["test".to_string(), "failed".text_red(), "8.7s".to_string()])
+///     .border(Border::Rounded)
+///     .stripes(Color::Ansi256(236))
+///     .width(40);
+/// println!("{}", table);
+/// ```
+///
+/// [`Panel`]: crate::Panel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    columns: Vec<Column>,
+    rows: Vec<Vec<String>>,
+    border: Border,
+    border_style: Style,
+    header_style: Style,
+    stripes: Option<Color>,
+    width: Option<usize>,
+    padding: usize,
+}
+
+impl Default for Table {
+    fn default() -> Self {
+        Table::new()
+    }
+}
+
+impl Table {
+    /// an empty square bordered table with bold headers
+    pub fn new() -> Self {
+        Table {
+            columns: Vec::new(),
+            rows: Vec::new(),
+            border: Border::default(),
+            border_style: Style::new(),
+            header_style: Style::new().bold(),
+            stripes: None,
+            width: None,
+            padding: 1,
+        }
+    }
+
+    /// adds a column for every header
+    pub fn header<I>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Column>,
+    {
+        self.columns.extend(headers.into_iter().map(Into::into));
+        self
+    }
+
+    /// adds one column
+    pub fn column(mut self, column: impl Into<Column>) -> Self {
+        self.columns.push(column.into());
+        self
+    }
+
+    /// adds a row of cells, each may span several lines
+    pub fn row<I>(mut self, cells: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        self.rows
+            .push(cells.into_iter().map(|c| c.to_string()).collect());
+        self
+    }
+
+    /// the characters the table is drawn with, `Border::None` leaves out
+    /// the edges and the line under the header
+    pub fn border(mut self, border: Border) -> Self {
+        self.border = border;
+        self
+    }
+
+    /// the style of the border
+    pub fn border_style(mut self, style: Style) -> Self {
+        self.border_style = style;
+        self
+    }
+
+    /// the style of the header row, bold by default
+    pub fn header_style(mut self, style: Style) -> Self {
+        self.header_style = style;
+        self
+    }
+
+    /// gives every other row `color` as background, zebra style
+    pub fn stripes(mut self, color: Color) -> Self {
+        self.stripes = Some(color);
+        self
+    }
+
+    /// makes the table `width` columns wide, border included
+    ///
+    /// wider columns shrink down to their minimum to fit, and flexible
+    /// columns share the room left over
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// spaces on either side of every cell, 1 by default
+    pub fn padding(mut self, padding: usize) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// the columns with ones added for cells past the last header
+    fn columns(&self) -> Vec<Column> {
+        let count = self.rows.iter().map(Vec::len).max().unwrap_or(0);
+        let mut columns = self.columns.clone();
+        while columns.len() < count {
+            columns.push(Column::new(""));
+        }
+        columns
+    }
+
+    fn widths(&self, columns: &[Column]) -> Vec<usize> {
+        let widest = |cell: &str| {
+            ansi::lines(cell)
+                .iter()
+                .map(|line| display_width(line))
+                .max()
+                .unwrap_or(0)
+        };
+        let mut widths: Vec<usize> = columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                let cells = self.rows.iter().filter_map(|row| row.get(i));
+                let natural = cells
+                    .map(|c| widest(c))
+                    .fold(widest(&column.header), usize::max);
+                column.clamp(natural)
+            })
+            .collect();
+
+        let Some(width) = self.width else {
+            return widths;
+        };
+        let room = width.saturating_sub(self.chrome(columns.len()));
+        let mut used: usize = widths.iter().sum();
+
+        // the widest column that may still shrink gives up a column at a time
+        while used > room {
+            let widest = (0..columns.len())
+                .filter(|&i| widths[i] > columns[i].min.max(1))
+                .max_by_key(|&i| (widths[i], std::cmp::Reverse(i)));
+            let Some(i) = widest else { break };
+            widths[i] -= 1;
+            used -= 1;
+        }
+
+        // flexible columns take turns growing until they reach their maximum
+        while used < room {
+            let mut grew = false;
+            for (i, column) in columns.iter().enumerate() {
+                if used < room && column.flexible && column.max.is_none_or(|max| widths[i] < max) {
+                    widths[i] += 1;
+                    used += 1;
+                    grew = true;
+                }
+            }
+            if !grew {
+                break;
+            }
+        }
+        widths
+    }
+
+    /// columns taken by the border and padding
+    fn chrome(&self, columns: usize) -> usize {
+        let lines = match self.border {
+            Border::None => columns.saturating_sub(1),
+            _ => columns + 1,
+        };
+        lines + columns * 2 * self.padding
+    }
+
+    /// a line across the table, like the top edge or the line under the
+    /// header
+    fn rule(&self, widths: &[usize], [left, line, junction, right]: [char; 4]) -> String {
+        let mut rule = String::from(left);
+        for (i, width) in widths.iter().enumerate() {
+            if i > 0 {
+                rule.push(junction);
+            }
+            rule.extend(std::iter::repeat_n(line, width + 2 * self.padding));
+        }
+        rule.push(right);
+        self.border_style.paint(&rule).to_string()
+    }
+
+    fn row_lines(
+        &self,
+        chars: &BorderChars,
+        columns: &[Column],
+        widths: &[usize],
+        cells: &[String],
+        style: Style,
+    ) -> Vec<String> {
+        let cells: Vec<Vec<String>> = columns
+            .iter()
+            .zip(widths)
+            .enumerate()
+            .map(|(i, (column, &width))| {
+                let cell = cells.get(i).map_or("", String::as_str);
+                column.lines(cell, width.max(1))
+            })
+            .collect();
+        let height = cells.iter().map(Vec::len).max().unwrap_or(0).max(1);
+
+        let paint = |c: char| self.border_style.paint(&c).to_string();
+        let (left, vertical, right) = match self.border {
+            Border::None => (String::new(), " ".to_string(), String::new()),
+            _ => (paint(chars.left), paint(chars.vertical), paint(chars.right)),
+        };
+        let padding = " ".repeat(self.padding);
+
+        (0..height)
+            .map(|k| {
+                let mut line = left.clone();
+                for (i, column) in columns.iter().enumerate() {
+                    if i > 0 {
+                        line.push_str(&vertical);
+                    }
+                    let text = cells[i].get(k).map_or("", String::as_str);
+                    let text = column.align.pad(text, widths[i]);
+                    let cell = format!("{}{}{}", padding, text, padding);
+                    line.push_str(&style.paint(&cell).to_string());
+                }
+                line.push_str(&right);
+                line
+            })
+            .collect()
+    }
+}
+
+impl Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let columns = self.columns();
+        if columns.is_empty() {
+            return Ok(());
+        }
+        let widths = self.widths(&columns);
+        let chars = self.border.chars();
+        let bordered = self.border != Border::None;
+
+        let mut lines = Vec::new();
+        if bordered {
+            let top = [chars.top_left, chars.top, chars.top_tee, chars.top_right];
+            lines.push(self.rule(&widths, top));
+        }
+
+        if columns.iter().any(|c| !c.header.is_empty()) {
+            let headers: Vec<String> = columns.iter().map(|c| c.header.clone()).collect();
+            lines.extend(self.row_lines(&chars, &columns, &widths, &headers, self.header_style));
+            if bordered {
+                let rule = [
+                    chars.left_tee,
+                    chars.horizontal,
+                    chars.cross,
+                    chars.right_tee,
+                ];
+                lines.push(self.rule(&widths, rule));
+            }
+        }
+
+        for (i, row) in self.rows.iter().enumerate() {
+            let style = match self.stripes {
+                Some(color) if i % 2 == 1 => Style::new().bg(color),
+                _ => Style::new(),
+            };
+            lines.extend(self.row_lines(&chars, &columns, &widths, row, style));
+        }
+
+        if bordered {
+            let bottom = [
+                chars.bottom_left,
+                chars.bottom,
+                chars.bottom_tee,
+                chars.bottom_right,
+            ];
+            lines.push(self.rule(&widths, bottom));
+        }
+
+        for (i, line) in lines.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::term::{with_color_choice, ColorChoice};
+
+    fn widths(table: &Table) -> Vec<usize> {
+        table.widths(&table.columns())
+    }
+
+    #[test]
+    fn columns_fit_their_widest_cell() {
+        let table = Table::new().header(["a", "bb"]).row(["xxxx", "y", "z"]);
+        assert_eq!(widths(&table), [4, 2, 1]);
+        // a column added later takes the place of the one for the extra cell
+        let table = table.column(Column::new("").width(3));
+        assert_eq!(widths(&table), [4, 2, 3]);
+    }
+
+    #[test]
+    fn the_widest_column_shrinks_first() {
+        // 2 columns take 7 columns of border and padding
+        let table = Table::new().header(["name", "x"]).row(["abcdefghij", "y"]);
+        assert_eq!(widths(&table.clone().width(15)), [7, 1]);
+        let table = Table::new().header(["aaaaa", "bbbbb"]).width(15);
+        assert_eq!(widths(&table), [4, 4]);
+        assert_eq!(widths(&table.width(16)), [4, 5]);
+    }
+
+    #[test]
+    fn minimum_widths_are_kept() {
+        let table = Table::new()
+            .column(Column::new("name").min_width(9))
+            .column("x")
+            .row(["abcdefghij", "y"])
+            .width(15);
+        assert_eq!(widths(&table), [9, 1]);
+    }
+
+    #[test]
+    fn flexible_columns_share_the_room() {
+        // 3 columns take 10 columns of border and padding
+        let table = Table::new()
+            .column(Column::new("a").flexible())
+            .column(Column::new("b").flexible().max_width(3))
+            .column("c")
+            .width(20);
+        assert_eq!(widths(&table), [6, 3, 1]);
+    }
+
+    #[test]
+    fn shrunk_cells_wrap() {
+        let table = Table::new()
+            .header(["name", "size"])
+            .row(["beautify crate", "12"])
+            .width(19);
+        let drawn = with_color_choice(ColorChoice::Never, || table.to_string());
+        assert_eq!(
+            drawn,
+            "┌──────────┬──────┐\n\
+             │ name     │ size │\n\
+             ├──────────┼──────┤\n\
+             │ beautify │ 12   │\n\
+             │ crate    │      │\n\
+             └──────────┴──────┘"
+        );
+    }
+}
@@ -0,0 +1,359 @@
+use std::cell::Cell;
+use std::env;
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+
+/// how many colors the terminal can show
+///
+/// colors are downsampled to the current level right before they are
+/// emitted, so `text_blue_500()` turns into the closest 256 or 16 color
+/// code on terminals that can't show 24-bit color
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ColorLevel {
+    /// the 16 basic colors, e.g. the linux console
+    Ansi16,
+    /// the xterm 256 color palette
+    Ansi256,
+    /// 24-bit color
+    TrueColor,
+}
+
+// 0 means no override, otherwise the level's index + 1
+static OVERRIDE: AtomicU8 = AtomicU8::new(0);
+static DETECTED: OnceLock<ColorLevel> = OnceLock::new();
+
+impl ColorLevel {
+    /// detects the level from the environment
+    ///
+    /// looks at `FORCE_COLOR`, `COLORTERM`, `TERM`, `TERM_PROGRAM` and a few
+    /// well known terminal and ci variables, falling back to 16 colors
+    pub fn detect() -> Self {
+        Self::detect_with(|key| env::var(key).ok())
+    }
+
+    /// same as [`ColorLevel::detect`], reading variables through `var`
+    /// instead of the process environment
+    pub fn detect_with<F>(var: F) -> Self
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        match var("FORCE_COLOR").as_deref() {
+            Some("1") => return ColorLevel::Ansi16,
+            Some("2") => return ColorLevel::Ansi256,
+            Some("3") => return ColorLevel::TrueColor,
+            _ => {}
+        }
+
+        if let Some(colorterm) = var("COLORTERM") {
+            let colorterm = colorterm.to_ascii_lowercase();
+            if colorterm == "truecolor" || colorterm == "24bit" {
+                return ColorLevel::TrueColor;
+            }
+        }
+
+        let term = var("TERM").unwrap_or_default().to_ascii_lowercase();
+        if term == "linux" || term == "dumb" {
+            return ColorLevel::Ansi16;
+        }
+        if term.contains("direct") || term.contains("truecolor") || term.contains("24bit") {
+            return ColorLevel::TrueColor;
+        }
+
+        // terminals known to support 24-bit color
+        let truecolor_terms = [
+            "xterm-kitty",
+            "xterm-ghostty",
+            "alacritty",
+            "wezterm",
+            "foot",
+        ];
+        if truecolor_terms.iter().any(|t| term.starts_with(t)) {
+            return ColorLevel::TrueColor;
+        }
+        if var("WT_SESSION").is_some() || var("KONSOLE_VERSION").is_some() {
+            return ColorLevel::TrueColor;
+        }
+        if var("ConEmuANSI").as_deref() == Some("ON") {
+            return ColorLevel::TrueColor;
+        }
+
+        match var("TERM_PROGRAM").as_deref() {
+            Some("iTerm.app") | Some("vscode") | Some("WezTerm") | Some("ghostty")
+            | Some("Hyper") => return ColorLevel::TrueColor,
+            Some("Apple_Terminal") => return ColorLevel::Ansi256,
+            _ => {}
+        }
+
+        // ci runners known to render 24-bit color in their logs
+        if var("CI").is_some()
+            && (var("GITHUB_ACTIONS").is_some() || var("GITEA_ACTIONS").is_some())
+        {
+            return ColorLevel::TrueColor;
+        }
+
+        // other ci runners get 16 colors, unless their terminal says more
+        if term.contains("256") {
+            return ColorLevel::Ansi256;
+        }
+
+        ColorLevel::Ansi16
+    }
+}
+
+thread_local! {
+    static SCOPED_LEVEL: Cell<Option<ColorLevel>> = const { Cell::new(None) };
+}
+
+/// the level colors are currently emitted at on this thread, a scoped one
+/// set with [`with_color_level`] wins over the one set with
+/// [`set_color_level`], which wins over the detected one
+pub fn color_level() -> ColorLevel {
+    if let Some(level) = SCOPED_LEVEL.with(Cell::get) {
+        return level;
+    }
+    match OVERRIDE.load(Ordering::Relaxed) {
+        1 => ColorLevel::Ansi16,
+        2 => ColorLevel::Ansi256,
+        3 => ColorLevel::TrueColor,
+        _ => *DETECTED.get_or_init(ColorLevel::detect),
+    }
+}
+
+/// overrides the detected color level, `None` goes back to detection
+///
+/// example:
+/// ```rs
+/// set_color_level(Some(ColorLevel::Ansi256));
+/// ```
+pub fn set_color_level(level: Option<ColorLevel>) {
+    let value = match level {
+        None => 0,
+        Some(ColorLevel::Ansi16) => 1,
+        Some(ColorLevel::Ansi256) => 2,
+        Some(ColorLevel::TrueColor) => 3,
+    };
+    OVERRIDE.store(value, Ordering::Relaxed);
+}
+
+/// runs `f` with `level` in effect on the current thread only
+///
+/// example:
+/// ```rs
+/// let s = with_color_level(ColorLevel::Ansi256, || "hi!".text_blue_500());
+/// ```
+pub fn with_color_level<R>(level: ColorLevel, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<ColorLevel>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SCOPED_LEVEL.with(|scoped| scoped.set(self.0));
+        }
+    }
+
+    let _restore = Restore(SCOPED_LEVEL.with(|scoped| scoped.replace(Some(level))));
+    f()
+}
+
+/// whether colors and styles are emitted at all
+///
+/// when colors are off every method returns its text without any escape
+/// sequences
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ColorChoice {
+    /// always emit escapes
+    Always,
+    /// never emit escapes
+    Never,
+    /// emit escapes when the output stream is a terminal, honoring
+    /// `NO_COLOR`, `CLICOLOR`, `CLICOLOR_FORCE` and `TERM=dumb`
+    #[default]
+    Auto,
+}
+
+/// stream `ColorChoice::Auto` checks for a terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Stream {
+    /// standard output
+    #[default]
+    Stdout,
+    /// standard error
+    Stderr,
+}
+
+// 0 auto, 1 always, 2 never
+static CHOICE: AtomicU8 = AtomicU8::new(0);
+// 0 stdout, 1 stderr
+static STREAM: AtomicU8 = AtomicU8::new(0);
+static STDOUT_AUTO: OnceLock<bool> = OnceLock::new();
+static STDERR_AUTO: OnceLock<bool> = OnceLock::new();
+
+thread_local! {
+    static SCOPED: Cell<Option<ColorChoice>> = const { Cell::new(None) };
+}
+
+impl ColorChoice {
+    /// resolves the choice for `stream`, `Auto` looks at the environment
+    pub fn enabled(self, stream: Stream) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                let cache = match stream {
+                    Stream::Stdout => &STDOUT_AUTO,
+                    Stream::Stderr => &STDERR_AUTO,
+                };
+                *cache.get_or_init(|| {
+                    let tty = match stream {
+                        Stream::Stdout => io::stdout().is_terminal(),
+                        Stream::Stderr => io::stderr().is_terminal(),
+                    };
+                    auto_enabled(|key| env::var(key).ok(), tty)
+                })
+            }
+        }
+    }
+}
+
+/// decides `ColorChoice::Auto`, `CLICOLOR_FORCE` wins over everything,
+/// then `NO_COLOR`, `CLICOLOR=0` and `TERM=dumb` turn colors off
+fn auto_enabled<F>(var: F, tty: bool) -> bool
+where
+    F: Fn(&str) -> Option<String>,
+{
+    if var("CLICOLOR_FORCE").is_some_and(|v| !v.is_empty() && v != "0") {
+        return true;
+    }
+    if var("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+        return false;
+    }
+    if var("CLICOLOR").as_deref() == Some("0") {
+        return false;
+    }
+    if var("TERM").as_deref() == Some("dumb") {
+        return false;
+    }
+    tty
+}
+
+/// the color choice in effect on this thread, a scoped one set with
+/// [`with_color_choice`] wins over the global one
+pub fn color_choice() -> ColorChoice {
+    if let Some(choice) = SCOPED.with(Cell::get) {
+        return choice;
+    }
+    match CHOICE.load(Ordering::Relaxed) {
+        1 => ColorChoice::Always,
+        2 => ColorChoice::Never,
+        _ => ColorChoice::Auto,
+    }
+}
+
+/// sets the color choice for the whole program
+///
+/// example:
+/// ```rs
+/// if args.no_color {
+///     set_color_choice(ColorChoice::Never);
+/// }
+/// ```
+pub fn set_color_choice(choice: ColorChoice) {
+    let value = match choice {
+        ColorChoice::Auto => 0,
+        ColorChoice::Always => 1,
+        ColorChoice::Never => 2,
+    };
+    CHOICE.store(value, Ordering::Relaxed);
+}
+
+/// sets the stream `ColorChoice::Auto` checks, stdout by default
+pub fn set_color_stream(stream: Stream) {
+    let value = match stream {
+        Stream::Stdout => 0,
+        Stream::Stderr => 1,
+    };
+    STREAM.store(value, Ordering::Relaxed);
+}
+
+/// runs `f` with `choice` in effect on the current thread only
+///
+/// example:
+/// ```rs
+/// let plain = with_color_choice(ColorChoice::Never, || "hi!".text_red());
+/// assert_eq!(plain, "hi!");
+/// ```
+pub fn with_color_choice<R>(choice: ColorChoice, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<ColorChoice>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SCOPED.with(|scoped| scoped.set(self.0));
+        }
+    }
+
+    let _restore = Restore(SCOPED.with(|scoped| scoped.replace(Some(choice))));
+    f()
+}
+
+/// whether escapes are emitted right now on this thread
+pub fn colors_enabled() -> bool {
+    let stream = match STREAM.load(Ordering::Relaxed) {
+        1 => Stream::Stderr,
+        _ => Stream::Stdout,
+    };
+    color_choice().enabled(stream)
+}
+
+/// whether escapes are emitted right now on this thread to an output that
+/// is or isn't a terminal, for outputs other than the color stream
+pub(crate) fn colors_enabled_for(terminal: bool) -> bool {
+    match color_choice() {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => auto_enabled(|key| env::var(key).ok(), terminal),
+    }
+}
+
+/// runs `f` with colors on at 24-bit, on the current thread only so tests
+/// running in parallel don't see it
+#[cfg(test)]
+pub(crate) fn colored<R>(f: impl FnOnce() -> R) -> R {
+    with_color_level(ColorLevel::TrueColor, || {
+        with_color_choice(ColorChoice::Always, f)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(vars: &[(&str, &str)]) -> ColorLevel {
+        ColorLevel::detect_with(|key| {
+            vars.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.to_string())
+        })
+    }
+
+    #[test]
+    fn term_wins_over_ci_default() {
+        assert_eq!(
+            detect(&[("CI", "true"), ("TERM", "xterm-256color")]),
+            ColorLevel::Ansi256
+        );
+        assert_eq!(
+            detect(&[("CI", "true"), ("TERM", "xterm")]),
+            ColorLevel::Ansi16
+        );
+        assert_eq!(
+            detect(&[("CI", "true"), ("GITHUB_ACTIONS", "true")]),
+            ColorLevel::TrueColor
+        );
+        assert_eq!(
+            detect(&[
+                ("CI", "true"),
+                ("GITHUB_ACTIONS", "true"),
+                ("TERM", "xterm-256color")
+            ]),
+            ColorLevel::TrueColor
+        );
+    }
+}
@@ -0,0 +1,211 @@
+use crate::ansi::{self, Segment};
+use crate::style::Style;
+use crate::width::{display_width, grapheme_width, graphemes};
+
+/// which part of the text is replaced by the ellipsis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TruncateAt {
+    /// keeps the end, `…/src/main.rs`
+    Start,
+    /// keeps both ends, `/home/…/main.rs`
+    Middle,
+    /// keeps the start, `/home/user/…`
+    #[default]
+    End,
+}
+
+/// settings for `Colors::truncate_width`
+///
+/// a plain width converts into options with the defaults, cutting the end
+/// and marking it with `…`
+///
+/// example:
+/// ```rs
+/// let path = "/home/user/projects/beautify/src/main.rs".text_blue();
+/// println!("{}", path.truncate_width(TruncateOptions::new(24).at(TruncateAt::Middle)));
+/// println!("{}", message.truncate_width(TruncateOptions::new(40).ellipsis("...")));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TruncateOptions {
+    width: usize,
+    at: TruncateAt,
+    ellipsis: String,
+}
+
+impl TruncateOptions {
+    /// cuts text to `width` columns, ellipsis included
+    pub fn new(width: usize) -> Self {
+        TruncateOptions {
+            width,
+            at: TruncateAt::default(),
+            ellipsis: "…".to_string(),
+        }
+    }
+
+    /// which part of the text is cut
+    pub fn at(mut self, at: TruncateAt) -> Self {
+        self.at = at;
+        self
+    }
+
+    /// what marks the cut, `…` by default, may be empty or styled
+    pub fn ellipsis(mut self, ellipsis: impl Into<String>) -> Self {
+        self.ellipsis = ellipsis.into();
+        self
+    }
+}
+
+impl From<usize> for TruncateOptions {
+    fn from(width: usize) -> Self {
+        TruncateOptions::new(width)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Item<'a> {
+    Escape(&'a str),
+    Grapheme(&'a str, usize),
+}
+
+/// cuts every line of `text` that is wider than the options allow
+pub(crate) fn truncate(text: &str, options: &TruncateOptions) -> String {
+    if text.lines().count() <= 1 {
+        return truncate_line(text, options);
+    }
+    ansi::lines(text)
+        .iter()
+        .map(|line| truncate_line(line, options))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn truncate_line(line: &str, options: &TruncateOptions) -> String {
+    if display_width(line) <= options.width {
+        return line.to_string();
+    }
+
+    let ellipsis_width = display_width(&options.ellipsis);
+    if ellipsis_width > options.width {
+        // not even the ellipsis fits, show what does of it
+        return truncate_line(
+            &options.ellipsis,
+            &TruncateOptions::new(options.width).ellipsis(""),
+        );
+    }
+
+    let mut items = Vec::new();
+    for segment in ansi::segments(line) {
+        match segment {
+            Segment::Escape(e) => items.push(Item::Escape(e)),
+            Segment::Text(t) => {
+                items.extend(graphemes(t).map(|g| Item::Grapheme(g, grapheme_width(g))));
+            }
+        }
+    }
+
+    let room = options.width - ellipsis_width;
+    let (head_room, tail_room) = match options.at {
+        TruncateAt::Start => (0, room),
+        TruncateAt::Middle => (room - room / 2, room / 2),
+        TruncateAt::End => (room, 0),
+    };
+
+    // the head ends after the last grapheme that fits
+    let mut used = 0;
+    let mut head = 0;
+    for (i, item) in items.iter().enumerate() {
+        if let Item::Grapheme(_, w) = item {
+            if used + w > head_room {
+                break;
+            }
+            used += w;
+            head = i + 1;
+        }
+    }
+
+    // the tail starts at the last grapheme that still fits, escapes before
+    // it are replayed through the style they leave behind
+    let mut used = 0;
+    let mut tail = items.len();
+    for (i, item) in items.iter().enumerate().skip(head).rev() {
+        if let Item::Grapheme(_, w) = item {
+            if used + w > tail_room {
+                break;
+            }
+            used += w;
+            tail = i;
+        }
+    }
+
+    let mut result = String::new();
+    let mut active = Style::new();
+    for item in &items[..head] {
+        match item {
+            Item::Escape(e) => {
+                ansi::track_sgr(&mut active, e);
+                result.push_str(e);
+            }
+            Item::Grapheme(g, _) => result.push_str(g),
+        }
+    }
+    if !active.is_plain() {
+        result.push_str("\x1B[0m");
+    }
+    result.push_str(&options.ellipsis);
+
+    if tail < items.len() {
+        for item in &items[head..tail] {
+            if let Item::Escape(e) = item {
+                ansi::track_sgr(&mut active, e);
+            }
+        }
+        result.push_str(&active.prefix());
+        for item in &items[tail..] {
+            match item {
+                Item::Escape(e) | Item::Grapheme(e, _) => result.push_str(e),
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::term::colored;
+
+    #[test]
+    fn cuts_where_asked() {
+        let path = "/home/user/main.rs";
+        let at = |at| truncate(path, &TruncateOptions::new(10).at(at));
+        assert_eq!(at(TruncateAt::End), "/home/use…");
+        assert_eq!(at(TruncateAt::Middle), "/home…n.rs");
+        assert_eq!(at(TruncateAt::Start), "…r/main.rs");
+        assert_eq!(truncate(path, &18.into()), path);
+    }
+
+    #[test]
+    fn counts_columns() {
+        assert_eq!(truncate("日本語", &4.into()), "日…");
+        assert_eq!(truncate("abcdef\nab", &4.into()), "abc…\nab");
+        assert_eq!(
+            truncate("hello", &TruncateOptions::new(2).ellipsis("...")),
+            ".."
+        );
+        assert_eq!(
+            truncate("hello", &TruncateOptions::new(3).ellipsis("")),
+            "hel"
+        );
+    }
+
+    #[test]
+    fn styles_are_closed_and_replayed() {
+        let s = "\x1B[31mhello world\x1B[0m";
+        assert_eq!(
+            colored(|| truncate(s, &8.into())),
+            "\x1B[31mhello w\x1B[0m…"
+        );
+        let start = TruncateOptions::new(6).at(TruncateAt::Start);
+        assert_eq!(colored(|| truncate(s, &start)), "…\x1B[31mworld\x1B[0m");
+    }
+}
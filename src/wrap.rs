@@ -0,0 +1,273 @@
+use crate::ansi::{self, Segment};
+use crate::style::Style;
+use crate::width::{display_width, grapheme_width, graphemes};
+
+/// where lines may be broken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum WrapMode {
+    /// between words, words longer than a line are broken between
+    /// characters
+    #[default]
+    Word,
+    /// between any two characters
+    Char,
+}
+
+/// settings for `Colors::wrap` and `Colors::fill`
+///
+/// a plain width converts into options with the defaults, so both
+/// `text.wrap(40)` and `text.wrap(WrapOptions::new(40).mode(WrapMode::Char))`
+/// work
+///
+/// example:
+/// ```rs
+/// // a bullet point with a hanging indent
+/// let options = WrapOptions::new(40).initial_indent("- ").subsequent_indent("  ");
+/// println!("{}", text.fill(options));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrapOptions {
+    width: usize,
+    mode: WrapMode,
+    initial_indent: String,
+    subsequent_indent: String,
+}
+
+impl WrapOptions {
+    /// wraps between words to `width` columns
+    pub fn new(width: usize) -> Self {
+        WrapOptions {
+            width,
+            mode: WrapMode::default(),
+            initial_indent: String::new(),
+            subsequent_indent: String::new(),
+        }
+    }
+
+    /// where lines may be broken
+    pub fn mode(mut self, mode: WrapMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// put in front of the first line of every paragraph, counts towards
+    /// the width
+    pub fn initial_indent(mut self, indent: impl Into<String>) -> Self {
+        self.initial_indent = indent.into();
+        self
+    }
+
+    /// put in front of every other line, counts towards the width
+    pub fn subsequent_indent(mut self, indent: impl Into<String>) -> Self {
+        self.subsequent_indent = indent.into();
+        self
+    }
+}
+
+impl From<usize> for WrapOptions {
+    fn from(width: usize) -> Self {
+        WrapOptions::new(width)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Item<'a> {
+    Escape(&'a str),
+    Grapheme(&'a str, usize),
+}
+
+/// breaks `text` into lines no wider than the options allow
+///
+/// existing line breaks are kept, and styles still in effect at a break are
+/// closed at the end of the line and opened again after the indent on the
+/// next one
+pub(crate) fn wrap(text: &str, options: &WrapOptions) -> Vec<String> {
+    let mut lines = Vec::new();
+    for line in ansi::lines(text) {
+        let mut wrapper = Wrapper::new(options, &mut lines);
+        for segment in ansi::segments(&line) {
+            match segment {
+                Segment::Escape(e) => wrapper.push(Item::Escape(e)),
+                Segment::Text(t) => {
+                    for g in graphemes(t) {
+                        wrapper.push(Item::Grapheme(g, grapheme_width(g)));
+                    }
+                }
+            }
+        }
+        wrapper.finish();
+    }
+    lines
+}
+
+struct Wrapper<'a, 'o> {
+    options: &'o WrapOptions,
+    lines: &'o mut Vec<String>,
+    line: String,
+    // columns used on the current line, not counting the indent
+    used: usize,
+    // columns available on the current line
+    room: usize,
+    // the style in effect at the end of the line so far
+    active: Style,
+    // whitespace and the word after it, not placed yet
+    space: Vec<Item<'a>>,
+    word: Vec<Item<'a>>,
+}
+
+impl<'a, 'o> Wrapper<'a, 'o> {
+    fn new(options: &'o WrapOptions, lines: &'o mut Vec<String>) -> Self {
+        Wrapper {
+            options,
+            lines,
+            line: options.initial_indent.clone(),
+            used: 0,
+            room: room(options, &options.initial_indent),
+            active: Style::new(),
+            space: Vec::new(),
+            word: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, item: Item<'a>) {
+        match item {
+            Item::Grapheme(g, _) if g.starts_with(char::is_whitespace) => {
+                self.place_word();
+                self.space.push(item);
+            }
+            Item::Grapheme(..) => {
+                self.word.push(item);
+                if self.options.mode == WrapMode::Char {
+                    self.place_word();
+                }
+            }
+            // escapes between words go with the next word, so a style that
+            // starts a word moves to the next line with it
+            Item::Escape(_) => self.word.push(item),
+        }
+    }
+
+    fn finish(mut self) {
+        self.place_word();
+        // trailing whitespace is dropped, escapes in it are kept
+        for item in std::mem::take(&mut self.space) {
+            if let Item::Escape(e) = item {
+                self.emit_escape(e);
+            }
+        }
+        self.lines.push(self.line);
+    }
+
+    fn place_word(&mut self) {
+        if self.word.is_empty() {
+            return;
+        }
+        let space = std::mem::take(&mut self.space);
+        let word = std::mem::take(&mut self.word);
+        let width = |items: &[Item<'_>]| -> usize {
+            items
+                .iter()
+                .map(|item| match item {
+                    Item::Grapheme(_, w) => *w,
+                    Item::Escape(_) => 0,
+                })
+                .sum()
+        };
+
+        let (space_width, word_width) = (width(&space), width(&word));
+        let fits = self.used + space_width + word_width <= self.room;
+        if self.used > 0 && !fits {
+            self.break_line();
+        }
+
+        // whitespace at the start of a line is dropped
+        for item in space {
+            match item {
+                Item::Escape(e) => self.emit_escape(e),
+                Item::Grapheme(g, w) if self.used > 0 => self.emit(g, w),
+                Item::Grapheme(..) => {}
+            }
+        }
+        for item in word {
+            match item {
+                Item::Escape(e) => self.emit_escape(e),
+                Item::Grapheme(g, w) => {
+                    // a word longer than a whole line
+                    if self.used > 0 && self.used + w > self.room {
+                        self.break_line();
+                    }
+                    self.emit(g, w);
+                }
+            }
+        }
+    }
+
+    fn emit(&mut self, grapheme: &str, width: usize) {
+        self.line.push_str(grapheme);
+        self.used += width;
+    }
+
+    fn emit_escape(&mut self, escape: &str) {
+        ansi::track_sgr(&mut self.active, escape);
+        self.line.push_str(escape);
+    }
+
+    fn break_line(&mut self) {
+        let indent = &self.options.subsequent_indent;
+        let mut next = indent.clone();
+        next.push_str(&self.active.prefix());
+        if !self.active.is_plain() {
+            self.line.push_str("\x1B[0m");
+        }
+        self.lines.push(std::mem::replace(&mut self.line, next));
+        self.used = 0;
+        self.room = room(self.options, indent);
+    }
+}
+
+/// columns left for text after `indent`, at least one so every line makes
+/// progress
+fn room(options: &WrapOptions, indent: &str) -> usize {
+    options.width.saturating_sub(display_width(indent)).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::term::colored;
+
+    #[test]
+    fn breaks_between_words() {
+        assert_eq!(
+            wrap("the quick brown fox", &10.into()),
+            ["the quick", "brown fox"]
+        );
+        assert_eq!(wrap("one\n\ntwo", &10.into()), ["one", "", "two"]);
+        assert_eq!(wrap("abcdefghij", &4.into()), ["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("日本語", &4.into()), ["日本", "語"]);
+    }
+
+    #[test]
+    fn char_mode_fills_lines() {
+        let options = WrapOptions::new(4).mode(WrapMode::Char);
+        assert_eq!(wrap("ab cdef", &options), ["ab c", "def"]);
+        assert_eq!(wrap("ab cdef", &4.into()), ["ab", "cdef"]);
+    }
+
+    #[test]
+    fn indents_count_towards_the_width() {
+        let options = WrapOptions::new(10)
+            .initial_indent("- ")
+            .subsequent_indent("  ");
+        assert_eq!(
+            wrap("one two three four", &options),
+            ["- one two", "  three", "  four"]
+        );
+    }
+
+    #[test]
+    fn styles_are_carried_over_breaks() {
+        let lines = colored(|| wrap("\x1B[31mred text\x1B[0m", &4.into()));
+        assert_eq!(lines, ["\x1B[31mred\x1B[0m", "\x1B[31mtext\x1B[0m"]);
+    }
+}
@@ -0,0 +1,144 @@
+use std::fmt;
+
+use beautify::{with_color_choice, Color, ColorChoice, Colors, Style};
+
+fn colored<R>(f: impl FnOnce() -> R) -> R {
+    with_color_choice(ColorChoice::Always, f)
+}
+
+#[test]
+fn outer_style_resumes_after_inner_reset() {
+    let s = colored(|| format!("a {} b", "x".text_red()).bold());
+    assert_eq!(s, "\x1B[1ma \x1B[31mx\x1B[0m\x1B[1m b\x1B[0m");
+}
+
+#[test]
+fn attributes_and_colors_stack() {
+    let s = colored(|| "x".text_red().bold());
+    assert_eq!(s, "\x1B[1m\x1B[31mx\x1B[0m");
+
+    let s = colored(|| "x".bold().text_red().bg_black().underline());
+    assert_eq!(s, "\x1B[4m\x1B[40m\x1B[31m\x1B[1mx\x1B[0m");
+}
+
+#[test]
+fn inner_style_overrides_only_what_it_sets() {
+    let s = colored(|| format!("a{}b", "x".text_blue()).text_red().bold());
+    assert_eq!(s, "\x1B[1m\x1B[31ma\x1B[34mx\x1B[0m\x1B[1m\x1B[31mb\x1B[0m");
+}
+
+#[test]
+fn deep_nesting_restores_each_level() {
+    let s = colored(|| {
+        let inner = "3".text_red();
+        let middle = format!("2{}2", inner).italic();
+        let outer = format!("1{}1", middle).underline();
+        format!("0{}0", outer).bg_blue()
+    });
+    assert_eq!(
+        s,
+        concat!(
+            "\x1B[44m0",
+            "\x1B[4m1",
+            "\x1B[3m2",
+            "\x1B[31m3\x1B[0m",
+            "\x1B[44m\x1B[4m\x1B[3m2\x1B[0m",
+            "\x1B[44m\x1B[4m1\x1B[0m",
+            "\x1B[44m0\x1B[0m",
+        )
+    );
+}
+
+#[test]
+fn reset_followed_by_parameters_keeps_them() {
+    let s = colored(|| "a\x1B[0;34mb".text_red());
+    assert_eq!(s, "\x1B[31ma\x1B[0;31;34mb\x1B[0m");
+}
+
+#[test]
+fn sgr_after_reset_is_applied_over_outer_style() {
+    let s = colored(|| "a\x1B[0m\x1B[34mb".text_red());
+    assert_eq!(s, "\x1B[31ma\x1B[0m\x1B[31m\x1B[34mb\x1B[0m");
+}
+
+#[test]
+fn zero_inside_extended_color_is_not_a_reset() {
+    let s = colored(|| "\x1B[38;5;0ma".bold());
+    assert_eq!(s, "\x1B[1m\x1B[38;5;0ma\x1B[0m");
+
+    let s = colored(|| "\x1B[48;2;0;0;0ma".bold());
+    assert_eq!(s, "\x1B[1m\x1B[48;2;0;0;0ma\x1B[0m");
+}
+
+#[test]
+fn raw_codes_nest_too() {
+    let s = colored(|| format!("a{}b", "x".bold()).text_default());
+    assert_eq!(s, "\x1B[39ma\x1B[1mx\x1B[0m\x1B[39mb\x1B[0m");
+}
+
+#[test]
+fn styled_and_string_api_agree() {
+    colored(|| {
+        let inner = "x".text_red();
+        let text = format!("a{}b", inner);
+        assert_eq!(text.styled().bold().to_string(), text.bold());
+        assert_eq!(
+            text.styled()
+                .with_style(Style::new().fg(Color::Ansi16(2)))
+                .to_string(),
+            text.text_green()
+        );
+    });
+}
+
+#[test]
+fn default_color_undoes_the_outer_one() {
+    colored(|| {
+        let plain = "x".text_default();
+        let styled = "x".styled().text_default().to_string();
+        assert_eq!(styled, plain);
+
+        let s = format!("a{}b", "x".styled().text_default()).text_red();
+        assert_eq!(s, "\x1B[31ma\x1B[39mx\x1B[0m\x1B[31mb\x1B[0m");
+        assert_eq!(format!("a{}b", plain).text_red(), s);
+
+        let s = format!("a{}b", "x".styled().bg_default()).bg_red();
+        assert_eq!(s, "\x1B[41ma\x1B[49mx\x1B[0m\x1B[41mb\x1B[0m");
+    });
+}
+
+#[test]
+fn other_escapes_pass_through() {
+    let link = "\x1B]8;;https://example.com\x1B\\link\x1B]8;;\x1B\\";
+    let s = colored(|| link.bold());
+    assert_eq!(s, format!("\x1B[1m{}\x1B[0m", link));
+
+    let s = colored(|| "a\x1B[2Kb".bold());
+    assert_eq!(s, "\x1B[1ma\x1B[2Kb\x1B[0m");
+}
+
+/// writes every byte on its own so escapes arrive in pieces
+struct Pieces(&'static str);
+
+impl fmt::Display for Pieces {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            write!(f, "{}", c)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn escapes_split_across_writes() {
+    let s = colored(|| Pieces("a\x1B[31mx\x1B[0mb").bold());
+    assert_eq!(s, "\x1B[1ma\x1B[31mx\x1B[0m\x1B[1mb\x1B[0m");
+}
+
+#[test]
+fn disabled_colors_leave_text_alone() {
+    let s = with_color_choice(ColorChoice::Never, || {
+        format!("a{}b", "x".text_red()).bold()
+    });
+    assert_eq!(s, "axb");
+}